@@ -0,0 +1,71 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+import java.io.IOException;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.Locale;
+import java.util.TreeSet;
+import java.util.regex.Matcher;
+import java.util.regex.Pattern;
+
+/**
+ * Writes the display name of every territory in the metadata, for every
+ * language of the geocoding data, from the CLDR data of the JDK. This is where
+ * libphonenumber's geocoder takes country names from as well.
+ *
+ * Run by `update.sh` after updating the metadata and geocoding data, or by
+ * hand from the `assets` directory with `java countries/Names.java`.
+ */
+public class Names {
+  public static void main(String[] args) throws IOException {
+    String metadata = Files.readString(Path.of("PhoneNumberMetadata.xml"));
+    Matcher matcher = Pattern.compile("<territory id=\"([A-Z]{2})\"").matcher(metadata);
+    TreeSet<String> ids = new TreeSet<>();
+
+    while (matcher.find()) {
+      ids.add(matcher.group(1));
+    }
+
+    List<String> languages = new ArrayList<>();
+
+    try (var dirs = Files.list(Path.of("geocoding"))) {
+      dirs.filter(Files::isDirectory).forEach(d -> languages.add(d.getFileName().toString()));
+    }
+
+    for (String language : languages) {
+      Locale locale = Locale.forLanguageTag(language.replace('_', '-'));
+      StringBuilder out = new StringBuilder();
+
+      out.append("# Generated by countries/Names.java from the CLDR data of Java ")
+          .append(Runtime.version().feature())
+          .append(".\n\n");
+
+      for (String id : ids) {
+        String name = new Locale("", id).getDisplayCountry(locale);
+
+        // Territories without a name are displayed as their ID.
+        if (!name.isEmpty() && !name.equals(id)) {
+          out.append(id).append('|').append(name).append('\n');
+        }
+      }
+
+      Files.writeString(
+          Path.of("countries", language + ".txt"), out.toString(), StandardCharsets.UTF_8);
+    }
+  }
+}
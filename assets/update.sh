@@ -1,6 +1,9 @@
 #!/bin/bash
 set -eu
 
+# The files are written next to this script.
+cd "$(dirname "$0")"
+
 GIT_REF=95dd52a6176b881a14bcad96bce279debf6871f3
 TMP_CLONE_DIR=$(mktemp -d)
 trap 'rm -rf "$TMP_CLONE_DIR"' EXIT
@@ -9,5 +12,13 @@ echo "Downloading libphonenumber repository (git ref ${GIT_REF})..."
 curl -sL "https://github.com/google/libphonenumber/archive/${GIT_REF}.tar.gz" | tar -C $TMP_CLONE_DIR -xz --strip-components=1
 
 cp -vf $TMP_CLONE_DIR/resources/*.xml .
-rm -rf carrier geocoding
-cp -r $TMP_CLONE_DIR/resources/carrier $TMP_CLONE_DIR/resources/geocoding .
\ No newline at end of file
+rm -rf carrier geocoding timezones
+cp -r $TMP_CLONE_DIR/resources/carrier $TMP_CLONE_DIR/resources/geocoding $TMP_CLONE_DIR/resources/timezones .
+
+if command -v java > /dev/null; then
+	echo "Writing country names..."
+	java countries/Names.java
+else
+	echo "warning: java is needed to write the country names, see countries/Names.java," \
+		"keeping the existing ones" >&2
+fi
@@ -1,10 +1,11 @@
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{BufReader, BufWriter};
 use std::path::Path;
 
 extern crate quick_xml as xml;
 extern crate regex;
+extern crate regex_syntax;
 extern crate thiserror;
 
 extern crate serde;
@@ -12,6 +13,9 @@ extern crate serde;
 extern crate serde_derive;
 extern crate bincode;
 
+#[cfg(any(feature = "geocoding", feature = "carrier"))]
+extern crate miniz_oxide;
+
 use bincode::Options;
 
 #[path = "src/metadata/loader.rs"]
@@ -20,19 +24,432 @@ mod loader;
 #[path = "src/error.rs"]
 mod error;
 
+#[cfg(any(feature = "geocoding", feature = "carrier", feature = "timezones"))]
+#[path = "src/prefix/trie.rs"]
+mod trie;
+
+#[cfg(feature = "compiled-patterns")]
+#[path = "src/metadata/program.rs"]
+mod program;
+
 fn main() {
-    let metadata = loader::load(BufReader::new(
-        File::open("assets/PhoneNumberMetadata.xml").expect("could not open metadata file"),
-    ))
-    .expect("failed to load metadata");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/metadata/loader.rs");
+    println!("cargo:rerun-if-changed=src/error.rs");
+    println!("cargo:rerun-if-changed=src/prefix/trie.rs");
+    println!("cargo:rerun-if-changed=src/metadata/program.rs");
+    println!("cargo:rerun-if-env-changed=PHONENUMBER_METADATA_XML");
+    println!("cargo:rerun-if-env-changed=PHONENUMBER_REGIONS");
+    println!("cargo:rerun-if-env-changed=PHONENUMBER_METADATA_VERSION");
+    println!("cargo:rerun-if-env-changed=PHONENUMBER_SHORT_NUMBER_METADATA_XML");
+    println!("cargo:rerun-if-env-changed=PHONENUMBER_ALTERNATE_FORMATS_XML");
+
+    // The short number and alternate format metadata are not used by this
+    // crate yet, so overriding them would silently do nothing.
+    for name in [
+        "PHONENUMBER_SHORT_NUMBER_METADATA_XML",
+        "PHONENUMBER_ALTERNATE_FORMATS_XML",
+    ] {
+        if env::var_os(name).is_some() {
+            println!(
+                "cargo:warning={} is ignored, only the main metadata is embedded",
+                name
+            );
+        }
+    }
+
+    // Use patched metadata instead of the bundled one, e.g.
+    // `PHONENUMBER_METADATA_XML=/path/to/PhoneNumberMetadata.xml`.
+    let path = env::var("PHONENUMBER_METADATA_XML")
+        .unwrap_or_else(|_| "assets/PhoneNumberMetadata.xml".into());
+
+    println!("cargo:rerun-if-changed={}", path);
+
+    version(env::var("PHONENUMBER_METADATA_XML").ok());
+
+    let mut metadata =
+        loader::load(BufReader::new(File::open(&path).unwrap_or_else(|e| {
+            panic!("could not open metadata file {}: {}", path, e)
+        })))
+        .unwrap_or_else(|e| panic!("failed to load metadata from {}: {}", path, e));
+
+    // Only embed the comma separated regions, e.g. `PHONENUMBER_REGIONS=DE,FR,BE`.
+    if let Ok(regions) = env::var("PHONENUMBER_REGIONS") {
+        let regions = regions
+            .split(',')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .collect::<Vec<_>>();
+
+        metadata = loader::retain(metadata, &regions);
+
+        for region in regions {
+            if !metadata
+                .iter()
+                .any(|m| m.id.as_deref() == Some(&*region.to_uppercase()))
+            {
+                println!(
+                    "cargo:warning=unknown region in PHONENUMBER_REGIONS: {}",
+                    region
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "timezones")]
+    timezones();
+    names();
+
+    #[cfg(feature = "geocoding")]
+    prefixes("geocoding", &metadata);
+
+    #[cfg(feature = "carrier")]
+    prefixes("carrier", &metadata);
 
     let mut out = BufWriter::new(
         File::create(Path::new(&env::var("OUT_DIR").unwrap()).join("database.bin"))
             .expect("could not create database file"),
     );
 
+    // Every region is encoded on its own, so it can be decoded on first use.
+    let regions = metadata
+        .iter()
+        .map(|meta| Region {
+            id: meta.id.clone().expect("missing region id"),
+            code: meta.country_code.expect("missing country code"),
+            main: meta.main_country_for_code,
+            metadata: bincode::options()
+                .with_varint_encoding()
+                .serialize(meta)
+                .expect("failed to serialize region"),
+            patterns: compile(meta),
+        })
+        .collect::<Vec<_>>();
+
     bincode::options()
         .with_varint_encoding()
-        .serialize_into(&mut out, &metadata)
+        .serialize_into(&mut out, &regions)
         .expect("failed to serialize database");
 }
+
+/// Record where the embedded metadata comes from, see `metadata::Version`.
+fn version(custom: Option<String>) {
+    if let Some(path) = custom {
+        println!("cargo:rustc-env=PHONENUMBER_METADATA_SOURCE={}", path);
+    } else {
+        println!("cargo:rerun-if-changed=assets/update.sh");
+
+        // The crate version carries the upstream release as build metadata.
+        if let Some(release) = env::var("CARGO_PKG_VERSION")
+            .ok()
+            .and_then(|v| v.split_once('+').map(|(_, r)| r.to_owned()))
+        {
+            println!("cargo:rustc-env=PHONENUMBER_METADATA_RELEASE={}", release);
+        }
+
+        // The git reference the assets were downloaded from.
+        if let Some(reference) = fs::read_to_string("assets/update.sh").ok().and_then(|s| {
+            s.lines()
+                .find_map(|l| l.strip_prefix("GIT_REF=").map(|r| r.trim().to_owned()))
+        }) {
+            println!(
+                "cargo:rustc-env=PHONENUMBER_METADATA_SOURCE=google/libphonenumber@{}",
+                reference
+            );
+        }
+    }
+
+    if let Ok(label) = env::var("PHONENUMBER_METADATA_VERSION") {
+        println!("cargo:rustc-env=PHONENUMBER_METADATA_VERSION={}", label);
+    }
+}
+
+/// An encoded region in the embedded database.
+#[derive(Serialize)]
+struct Region {
+    id: String,
+    code: u16,
+    main: bool,
+    #[serde(serialize_with = "bytes")]
+    metadata: Vec<u8>,
+    patterns: Vec<Pattern>,
+}
+
+/// A pattern of a region compiled at build time, kept apart from the metadata
+/// so it never shows up in `loader::Metadata`.
+#[derive(Serialize)]
+struct Pattern {
+    source: String,
+    #[serde(serialize_with = "bytes")]
+    program: Vec<u8>,
+}
+
+fn bytes<S: serde::Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(value)
+}
+
+/// Every pattern of the region, with the element or attribute it is from.
+#[cfg(feature = "compiled-patterns")]
+fn patterns(meta: &loader::Metadata) -> Vec<(&'static str, &str)> {
+    let descriptors = [
+        ("generalDesc", &meta.general),
+        ("fixedLine", &meta.fixed_line),
+        ("mobile", &meta.mobile),
+        ("tollFree", &meta.toll_free),
+        ("premiumRate", &meta.premium_rate),
+        ("sharedCost", &meta.shared_cost),
+        ("personalNumber", &meta.personal_number),
+        ("voip", &meta.voip),
+        ("pager", &meta.pager),
+        ("uan", &meta.uan),
+        ("emergency", &meta.emergency),
+        ("voicemail", &meta.voicemail),
+        ("shortCode", &meta.short_code),
+        ("standardRate", &meta.standard_rate),
+        ("carrierSpecific", &meta.carrier),
+        ("noInternationalDialling", &meta.no_international),
+    ];
+
+    let mut result = Vec::new();
+
+    for (field, desc) in descriptors {
+        if let Some(pattern) = desc.as_ref().and_then(|d| d.national_number.as_ref()) {
+            result.push((field, pattern.as_str()));
+        }
+    }
+
+    let patterns = [
+        ("internationalPrefix", &meta.international_prefix),
+        (
+            "nationalPrefixForParsing",
+            &meta.national_prefix_for_parsing,
+        ),
+        ("leadingDigits", &meta.leading_digits),
+    ];
+
+    for (field, pattern) in patterns {
+        if let Some(pattern) = pattern {
+            result.push((field, pattern.as_str()));
+        }
+    }
+
+    for format in meta.formats.iter().chain(&meta.international_formats) {
+        if let Some(pattern) = format.pattern.as_ref() {
+            result.push(("numberFormat/pattern", pattern.as_str()));
+        }
+
+        for pattern in &format.leading_digits {
+            result.push(("numberFormat/leadingDigits", pattern.as_str()));
+        }
+    }
+
+    result
+}
+
+/// Compile every pattern of the region, see `metadata::program`.
+#[cfg(feature = "compiled-patterns")]
+fn compile(meta: &loader::Metadata) -> Vec<Pattern> {
+    let mut result = Vec::<Pattern>::new();
+
+    for (field, pattern) in patterns(meta) {
+        if result.iter().any(|p| p.source == pattern) {
+            continue;
+        }
+
+        let program = program::Program::new(pattern).unwrap_or_else(|e| {
+            panic!(
+                "failed to compile pattern in {}/{}: {}",
+                meta.id.as_deref().unwrap_or("?"),
+                field,
+                e
+            )
+        });
+
+        result.push(Pattern {
+            source: pattern.to_owned(),
+            program: bincode::options()
+                .with_varint_encoding()
+                .serialize(&program)
+                .expect("failed to serialize pattern"),
+        });
+    }
+
+    result
+}
+
+#[cfg(not(feature = "compiled-patterns"))]
+fn compile(_meta: &loader::Metadata) -> Vec<Pattern> {
+    Vec::new()
+}
+
+/// Embed the time zones vendored by `assets/update.sh`.
+#[cfg(feature = "timezones")]
+fn timezones() {
+    let path = Path::new("assets/timezones/map_data.txt");
+    println!("cargo:rerun-if-changed={}", path.display());
+
+    let content = fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!(
+            "could not read {}, run assets/update.sh to vendor it or disable the \
+             `timezones` feature: {}",
+            path.display(),
+            e
+        )
+    });
+
+    let trie = trie::entries(&content)
+        .and_then(trie::Trie::new)
+        .unwrap_or_else(|e| panic!("failed to load {}: {}", path.display(), e));
+
+    let out = BufWriter::new(
+        File::create(Path::new(&env::var("OUT_DIR").unwrap()).join("timezones.bin"))
+            .expect("could not create time zones file"),
+    );
+
+    bincode::options()
+        .with_varint_encoding()
+        .serialize_into(out, &trie)
+        .expect("failed to serialize time zones");
+}
+
+/// Embed the country names in `assets/countries`, for the languages enabled
+/// with the `lang-*` features.
+fn names() {
+    let root = Path::new("assets/countries");
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let mut files = fs::read_dir(root)
+        .unwrap_or_else(|e| panic!("could not read {}: {}", root.display(), e))
+        .map(|entry| entry.expect("could not read country names").path())
+        .filter(|path| path.extension().is_some_and(|e| e == "txt"))
+        .collect::<Vec<_>>();
+    files.sort();
+
+    let mut languages = Vec::new();
+
+    for file in files {
+        let language = file.file_stem().unwrap().to_string_lossy().into_owned();
+        let feature = format!("CARGO_FEATURE_LANG_{}", language.to_uppercase());
+
+        if env::var_os(feature).is_none() {
+            continue;
+        }
+
+        let content = fs::read_to_string(&file)
+            .unwrap_or_else(|e| panic!("could not read {}: {}", file.display(), e));
+
+        let names = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.split_once('|')
+                    .map(|(id, name)| (id.to_owned(), name.to_owned()))
+                    .unwrap_or_else(|| panic!("invalid line in {}: {}", file.display(), line))
+            })
+            .collect::<Vec<_>>();
+
+        languages.push((language, names));
+    }
+
+    let out = BufWriter::new(
+        File::create(Path::new(&env::var("OUT_DIR").unwrap()).join("names.bin"))
+            .expect("could not create country names file"),
+    );
+
+    bincode::options()
+        .with_varint_encoding()
+        .serialize_into(out, &languages)
+        .expect("failed to serialize country names");
+}
+
+/// A compressed map of descriptions by prefix in the embedded data.
+#[cfg(any(feature = "geocoding", feature = "carrier"))]
+#[derive(Serialize)]
+struct Prefixes {
+    language: String,
+    code: u16,
+    #[serde(serialize_with = "bytes")]
+    trie: Vec<u8>,
+}
+
+/// Compress the prefix files in `assets/<kind>` into a trie per language and
+/// country code, for the languages enabled with the `lang-*` features and the
+/// embedded country codes.
+#[cfg(any(feature = "geocoding", feature = "carrier"))]
+fn prefixes(kind: &str, metadata: &[loader::Metadata]) {
+    let root = Path::new("assets").join(kind);
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let mut languages = fs::read_dir(&root)
+        .unwrap_or_else(|e| panic!("could not read {}: {}", root.display(), e))
+        .map(|entry| entry.expect("could not read prefix directory").path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+    languages.sort();
+
+    let mut maps = Vec::new();
+
+    for path in languages {
+        let language = path.file_name().unwrap().to_string_lossy().into_owned();
+        let feature = format!("CARGO_FEATURE_LANG_{}", language.to_uppercase());
+
+        if env::var_os(feature).is_none() {
+            continue;
+        }
+
+        let mut files = fs::read_dir(&path)
+            .unwrap_or_else(|e| panic!("could not read {}: {}", path.display(), e))
+            .map(|entry| entry.expect("could not read prefix file").path())
+            .filter(|path| path.extension().is_some_and(|e| e == "txt"))
+            .collect::<Vec<_>>();
+        files.sort();
+
+        for file in files {
+            let code = file
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u16>().ok())
+                .unwrap_or_else(|| panic!("invalid prefix file name {}", file.display()));
+
+            if !metadata.iter().any(|m| m.country_code == Some(code)) {
+                continue;
+            }
+
+            let content = fs::read_to_string(&file)
+                .unwrap_or_else(|e| panic!("could not read {}: {}", file.display(), e));
+
+            let trie = trie::entries(&content)
+                .and_then(trie::Trie::new)
+                .unwrap_or_else(|e| panic!("failed to load {}: {}", file.display(), e));
+
+            let trie = bincode::options()
+                .with_varint_encoding()
+                .serialize(&trie)
+                .expect("failed to serialize prefixes");
+
+            maps.push(Prefixes {
+                language: language.clone(),
+                code,
+                trie: miniz_oxide::deflate::compress_to_vec(&trie, 9),
+            });
+        }
+    }
+
+    if maps.is_empty() {
+        println!(
+            "cargo:warning=no {} data embedded, enable some of the lang-* features",
+            kind
+        );
+    }
+
+    let out = BufWriter::new(
+        File::create(Path::new(&env::var("OUT_DIR").unwrap()).join(format!("{}.bin", kind)))
+            .expect("could not create prefixes file"),
+    );
+
+    bincode::options()
+        .with_varint_encoding()
+        .serialize_into(out, &maps)
+        .expect("failed to serialize prefixes");
+}
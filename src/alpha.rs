@@ -0,0 +1,160 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::consts;
+use crate::parser::helper::AsCharExt;
+use crate::validator;
+
+/// Check if the provided string is a vanity (alpha) number, i.e. a viable
+/// phone number with at least three letters in it, such as "1-800-FLOWERS".
+///
+/// Any extension is ignored, so "1800 six-flag" is an alpha number while
+/// "1800 123-1234 ext. 1234" is not.
+pub fn is_alpha_number<S: AsRef<str>>(string: S) -> bool {
+    let string = string.as_ref();
+
+    if !validator::is_viable(string) {
+        return false;
+    }
+
+    let stripped = consts::EXTN_PATTERN
+        .find(string)
+        .map(|m| &string[..m.start()])
+        .unwrap_or(string);
+
+    consts::VALID_ALPHA_PHONE.is_match(stripped)
+}
+
+/// Convert all alpha characters in a number to their keypad digit
+/// equivalents, leaving any other character (digits, punctuation and
+/// whitespace) untouched.
+///
+/// For example "1-800-FLOWERS" becomes "1-800-3569377".
+pub fn convert_alpha_characters_in_number<S: AsRef<str>>(string: S) -> String {
+    string
+        .as_ref()
+        .chars()
+        .map(|c| consts::ALPHA_MAPPINGS.get(&c).cloned().unwrap_or(c))
+        .collect()
+}
+
+/// Suggest vanity spellings for the digits of a number using the given word
+/// list.
+///
+/// Every occurrence of a word's keypad digits in the number is replaced by the
+/// upper-cased word, dropping any punctuation between the spelled digits and
+/// keeping the rest of the formatting, so "1-800-356-9377" with the word
+/// "flowers" gives "1-800-FLOWERS". Words containing characters that cannot be
+/// dialled are skipped, and suggestions are returned in word list order.
+pub fn alpha_spellings<S, I, W>(string: S, words: I) -> Vec<String>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = W>,
+    W: AsRef<str>,
+{
+    let string = string.as_ref();
+
+    // Byte offsets and values of every digit in the number.
+    let digits = string
+        .char_indices()
+        .filter_map(|(i, c)| c.to_dec_digit().map(|d| (i, c.len_utf8(), d)))
+        .collect::<Vec<_>>();
+
+    let value = digits.iter().map(|&(_, _, d)| d).collect::<String>();
+    let mut result = Vec::new();
+
+    for word in words {
+        let word = word.as_ref();
+
+        let keys = word
+            .chars()
+            .map(|c| consts::ALPHA_PHONE_MAPPINGS.get(&c).cloned())
+            .collect::<Option<String>>();
+
+        let keys = match keys {
+            Some(keys) if !keys.is_empty() => keys,
+            _ => continue,
+        };
+
+        let mut offset = 0;
+        while let Some(index) = value[offset..].find(&keys) {
+            let first = offset + index;
+            let last = first + keys.len() - 1;
+
+            let (start, _, _) = digits[first];
+            let (end, len, _) = digits[last];
+
+            let mut spelled = String::with_capacity(string.len());
+            spelled.push_str(&string[..start]);
+            spelled.push_str(&word.to_uppercase());
+            spelled.push_str(&string[end + len..]);
+
+            result.push(spelled);
+            offset = first + 1;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::alpha;
+
+    #[test]
+    fn is_alpha_number() {
+        assert!(alpha::is_alpha_number("1800 six-flags"));
+        assert!(alpha::is_alpha_number("1800 six-flags ext. 1234"));
+        assert!(alpha::is_alpha_number("+800 six-flags"));
+        assert!(alpha::is_alpha_number("180 six-flags"));
+
+        assert!(!alpha::is_alpha_number("1800 123-1234"));
+        assert!(!alpha::is_alpha_number("1 six-flags"));
+        assert!(!alpha::is_alpha_number("18 six-flags"));
+        assert!(!alpha::is_alpha_number("1800 123-1234 extension: 1234"));
+        assert!(!alpha::is_alpha_number("+800 1234-1234"));
+    }
+
+    #[test]
+    fn convert_alpha_characters_in_number() {
+        assert_eq!(
+            "1-800-3569377",
+            alpha::convert_alpha_characters_in_number("1-800-FLOWERS")
+        );
+        assert_eq!(
+            "1800-222-333",
+            alpha::convert_alpha_characters_in_number("1800-ABC-DEF")
+        );
+        assert_eq!(
+            "0800 4 74992",
+            alpha::convert_alpha_characters_in_number("0800 4 pizza")
+        );
+    }
+
+    #[test]
+    fn alpha_spellings() {
+        assert_eq!(
+            vec!["1-800-FLOWERS".to_string()],
+            alpha::alpha_spellings("1-800-356-9377", ["flowers"])
+        );
+
+        assert_eq!(
+            vec!["0800 4 PIZZA".to_string(), "0800 4PIZZA".to_string()],
+            alpha::alpha_spellings("0800 4 74992", ["pizza", "4pizza"])
+        );
+
+        // Words which cannot be dialled never match.
+        assert!(alpha::alpha_spellings("1-800-356-9377", ["", "flow-ers"]).is_empty());
+    }
+}
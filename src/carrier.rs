@@ -15,6 +15,13 @@
 use std::fmt;
 use std::ops::Deref;
 
+#[cfg(feature = "carrier")]
+use crate::metadata::Database;
+#[cfg(feature = "carrier")]
+use crate::phone_number::{PhoneNumber, Type};
+#[cfg(feature = "carrier")]
+use crate::prefix::{self, Prefixes};
+
 /// A phone number carrier.
 #[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Hash, Debug)]
 pub struct Carrier(pub(crate) String);
@@ -44,3 +51,81 @@ impl fmt::Display for Carrier {
         write!(f, "{}", self.0)
     }
 }
+
+#[cfg(feature = "carrier")]
+const CARRIER: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/carrier.bin"));
+
+#[cfg(feature = "carrier")]
+lazy_static! {
+    static ref PREFIXES: Prefixes = Prefixes::embedded(CARRIER);
+}
+
+/// Get the name of the carrier the mobile phone number was originally
+/// allocated to, in the given language, e.g. `en` or `zh-TW`.
+///
+/// Only valid mobile and pager numbers have a carrier. Numbers may have been
+/// ported to another carrier since.
+///
+/// ```
+/// # #[cfg(feature = "lang-en")] {
+/// let number = phonenumber::parse(None, "+44 7400 123456").unwrap();
+///
+/// assert_eq!(Some("Three"), phonenumber::carrier::name(&number, "en"));
+/// # }
+/// ```
+#[cfg(feature = "carrier")]
+pub fn name(number: &PhoneNumber, language: &str) -> Option<&'static str> {
+    name_with(&Database::snapshot(), number, language)
+}
+
+/// Get the name of the carrier the mobile phone number was originally
+/// allocated to, in the given language, with the given `Database`.
+#[cfg(feature = "carrier")]
+pub fn name_with(
+    database: &Database,
+    number: &PhoneNumber,
+    language: &str,
+) -> Option<&'static str> {
+    match number.number_type(database) {
+        Type::Mobile | Type::FixedLineOrMobile | Type::Pager => (),
+        _ => return None,
+    }
+
+    let code = number.code().value();
+    let digits = format!("{}{}", code, number.national());
+
+    prefix::candidates(language)
+        .iter()
+        .find_map(|language| PREFIXES.get(language, code, &digits))
+}
+
+/// The languages with embedded carrier names, as enabled with the `lang-*`
+/// features.
+#[cfg(feature = "carrier")]
+pub fn languages() -> impl Iterator<Item = &'static str> {
+    PREFIXES.languages()
+}
+
+#[cfg(all(test, feature = "carrier", feature = "lang-en", feature = "lang-ru"))]
+mod test {
+    use crate::carrier::name;
+    use crate::country;
+    use crate::parser;
+
+    #[test]
+    fn mobile() {
+        let number = parser::parse(None, "+44 7400 123456").unwrap();
+        assert_eq!(Some("Three"), name(&number, "en"));
+        assert_eq!(None, name(&number, "de"));
+
+        let number = parser::parse(Some(country::KZ), "8 705 123 45 67").unwrap();
+        assert_eq!(Some("Билайн"), name(&number, "ru"));
+        assert_eq!(Some("Beeline"), name(&number, "en"));
+    }
+
+    #[test]
+    fn fixed_line() {
+        let number = parser::parse(None, "+44 20 7031 3000").unwrap();
+        assert_eq!(None, name(&number, "en"));
+    }
+}
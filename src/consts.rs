@@ -27,6 +27,11 @@ pub const MAX_LENGTH_FOR_NSN: usize = 17;
 /// The maximum length of the country calling code.
 pub const MAX_LENGTH_FOR_COUNTRY_CODE: usize = 3;
 
+/// The minimum number of digits before a spaced slash for it to separate two
+/// numbers in a list. Fewer digits, as in "030 / 123456", are an area code
+/// set apart from the rest of the number.
+pub const MIN_LENGTH_BEFORE_LIST_SLASH: usize = 7;
+
 /// Region-code for the unknown region.
 pub const UNKNOWN_REGION: &str = "ZZ";
 
@@ -47,6 +52,18 @@ pub const RFC3966_ISDN_SUBADDRESS: &str = ";isub=";
 
 pub const REGION_CODE_FOR_NON_GEO_ENTITY: &str = "001";
 
+/// The words that may label an extension, for use in regular expressions.
+/// Wide character versions are also provided after each ASCII version.
+pub const EXTN_LABELS: &str = r"e?xt(?:ensi(?:o\x{0301}?|\x{00F3}))?n?|\x{FF45}?\x{FF58}\x{FF54}\x{FF4E}?|int|anexo|\x{FF49}\x{FF4E}\x{FF54}";
+
+/// One-character symbols that may label an extension, for use in a character
+/// class of a regular expression.
+pub const EXTN_SYMBOLS: &str = r"x\x{FF58}#\x{FF03}~\x{FF5E}";
+
+/// What may separate an extension label from its digits, for use in regular
+/// expressions.
+pub const EXTN_LABEL_END: &str = r"[:\.\x{FF0E}]?[ \x{00A0}\t,-]*";
+
 lazy_static! {
     /// Map of country calling codes that use a mobile token before the area code. One example of when
     /// this is relevant is when determining the length of the national destination code, which should
@@ -286,6 +303,38 @@ lazy_static! {
     pub static ref SECOND_NUMBER_START: Regex =
         Regex::new(r"[\\/] *x").unwrap();
 
+    /// Regular expression of separators between phone numbers in a list, such
+    /// as "+1 650 253 0000, 650-253-0001". Slashes are only candidates when
+    /// surrounded by whitespace, since they are otherwise used as punctuation
+    /// within a number, e.g. "030/1234567".
+    pub static ref NUMBER_LIST_SEPARATOR: Regex =
+        Regex::new(r"[,;|\r\n]+|\s+[\\/]\s+").unwrap();
+
+    /// Regular expression of what may follow a "," or ";" in a list while
+    /// still belonging to the same number: a parameter of an RFC3966 URI, as
+    /// in "tel:+1-650-253-0000;ext=123", or an extension label followed by
+    /// digits, as in "+1 650 253 0000, ext. 123".
+    pub static ref NUMBER_LIST_CONTINUATION: Regex =
+        RegexBuilder::new(&format!(r"^(?:(?:{extn}|{isub}|{context})|[ \x{{00A0}}\t]*(?:{labels}|[{symbols}]){label_end}\d)",
+            extn = regex::escape(&RFC3966_EXTN_PREFIX[1..]),
+            isub = regex::escape(&RFC3966_ISDN_SUBADDRESS[1..]),
+            context = regex::escape(&RFC3966_PHONE_CONTEXT[1..]),
+            labels = EXTN_LABELS,
+            symbols = EXTN_SYMBOLS,
+            label_end = EXTN_LABEL_END))
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+
+    /// Regular expression of a trailing label without any digits in it, such
+    /// as the "(home)" in "650-253-0001 (home)" or the "Fax" in
+    /// "030 1234-5678 Fax".
+    pub static ref TRAILING_LABEL: Regex =
+        RegexBuilder::new(r"(?:\([^\d(]*\)?|\s(?:fax|tel|phone|mobile|mob|cell|home|work|office)\.?:?)\s*$")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+
     /// Regular expression of trailing characters that we want to remove. We
     /// remove all characters that are not alpha or numerical characters. The
     /// hash character is retained here, as it may signify the previous block was
@@ -319,10 +368,12 @@ lazy_static! {
     /// matching. Here we allow "comma" and "semicolon" as possible extension
     /// indicators. When matching, these are hardly ever used to indicate this.
     pub static ref EXTN_PATTERNS_FOR_PARSING: String =
-        format!(r"{rfc3966_extn_prefix}{capturing_extn_digits}|[ \x{{00A0}}\t,]*(?:e?xt(?:ensi(?:o\x{{0301}}?|\x{{00F3}}))?n?|\x{{FF45}}?\x{{FF58}}\x{{FF54}}\x{{FF4E}}?|[{symbols}]|int|anexo|\x{{FF49}}\x{{FF4E}}\x{{FF54}})[:\.\x{{FF0E}}]?[ \x{{00A0}}\t,-]*{capturing_extn_digits}#?|[- ]+({digits}{{1,5}})#",
+        format!(r"{rfc3966_extn_prefix}{capturing_extn_digits}|[ \x{{00A0}}\t,]*(?:{labels}|[{symbols}]){label_end}{capturing_extn_digits}#?|[- ]+({digits}{{1,5}})#",
             rfc3966_extn_prefix = RFC3966_EXTN_PREFIX,
             capturing_extn_digits = *CAPTURING_EXTN_DIGITS,
-            symbols = r",;x\x{FF58}#\x{FF03}~\x{FF5E}",
+            labels = EXTN_LABELS,
+            symbols = format!(",;{}", EXTN_SYMBOLS),
+            label_end = EXTN_LABEL_END,
             digits = *DIGITS);
 
     /// Regexp of all possible ways to write extensions, for use when parsing.
@@ -331,10 +382,12 @@ lazy_static! {
     ///
     /// One-character symbols that can be used to indicate an extension.
     pub static ref EXTN_PATTERNS_FOR_MATCHING: String =
-        format!(r"{rfc3966_extn_prefix}{capturing_extn_digits}|[ \x{{00A0}}\t,]*(?:e?xt(?:ensi(?:o\x{{0301}}?|\x{{00F3}}))?n?|\x{{FF45}}?\x{{FF58}}\x{{FF54}}\x{{FF4E}}?|[{symbols}]|int|anexo|\x{{FF49}}\x{{FF4E}}\x{{FF54}})[:\.\x{{FF0E}}]?[ \x{{00A0}}\t,-]*{capturing_extn_digits}#?|[- ]+({digits}{{1,5}})#",
+        format!(r"{rfc3966_extn_prefix}{capturing_extn_digits}|[ \x{{00A0}}\t,]*(?:{labels}|[{symbols}]){label_end}{capturing_extn_digits}#?|[- ]+({digits}{{1,5}})#",
             rfc3966_extn_prefix = RFC3966_EXTN_PREFIX,
             capturing_extn_digits = *CAPTURING_EXTN_DIGITS,
-            symbols = r"x\x{FF58}#\x{FF03}~\x{FF5E}",
+            labels = EXTN_LABELS,
+            symbols = EXTN_SYMBOLS,
+            label_end = EXTN_LABEL_END,
             digits = *DIGITS);
 
     /// Regexp of all known extension prefixes used by different regions followed
@@ -14,10 +14,34 @@
 
 //! Country related types.
 
-use strum::{AsRefStr, EnumString};
+use strum::{AsRefStr, EnumIter, EnumString, IntoEnumIterator};
 
 use std::str;
 
+use bincode::Options;
+use fnv::FnvHashMap;
+
+use crate::metadata::Database;
+use crate::prefix;
+
+const NAMES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/names.bin"));
+
+lazy_static! {
+    /// Country names by language and ID, for the languages enabled with the
+    /// `lang-*` features.
+    static ref LOCALIZED: FnvHashMap<&'static str, FnvHashMap<&'static str, &'static str>> = {
+        let names: Vec<(&'static str, Vec<(&'static str, &'static str)>)> = bincode::options()
+            .with_varint_encoding()
+            .deserialize(NAMES)
+            .expect("failed to decode embedded country names");
+
+        names
+            .into_iter()
+            .map(|(language, names)| (language, names.into_iter().collect()))
+            .collect()
+    };
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Hash, Debug)]
 pub struct Code {
     /// The country code value.
@@ -29,7 +53,7 @@ pub struct Code {
 
 /// The source from which the country code is derived. This is not set in the
 /// general parsing method, but in the method that parses and keeps raw_input.
-#[derive(Eq, PartialEq, Copy, Clone, Serialize, Deserialize, Hash, Debug)]
+#[derive(Eq, PartialEq, Copy, Clone, Serialize, Deserialize, Hash, Debug, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum Source {
     /// The country code is derived based on a phone number with a leading "+",
@@ -51,15 +75,10 @@ pub enum Source {
     /// format (without country code). For example, this would be set when
     /// parsing the French number "01 42 68 53 00", when the default country is
     /// supplied as France.
+    #[default]
     Default,
 }
 
-impl Default for Source {
-    fn default() -> Self {
-        Source::Default
-    }
-}
-
 impl Code {
     /// The country code number.
     pub fn value(&self) -> u16 {
@@ -79,7 +98,9 @@ impl From<Code> for u16 {
 }
 
 /// CLDR country IDs.
-#[derive(Eq, PartialEq, Copy, Clone, Serialize, Deserialize, Hash, Debug, EnumString, AsRefStr)]
+#[derive(
+    Eq, PartialEq, Copy, Clone, Serialize, Deserialize, Hash, Debug, EnumString, AsRefStr, EnumIter,
+)]
 pub enum Id {
     AC,
     AD,
@@ -329,3 +350,338 @@ pub enum Id {
 }
 
 pub use self::Id::*;
+
+impl Id {
+    /// Iterate over every country ID.
+    pub fn iter() -> impl Iterator<Item = Id> {
+        <Id as IntoEnumIterator>::iter()
+    }
+
+    /// The name of the country in the given language, e.g. `en`, `de-CH` or
+    /// `zh-TW`, if the language is enabled with its `lang-*` feature.
+    ///
+    /// No language is enabled by default, so without any `lang-*` feature
+    /// this always returns `None`.
+    ///
+    /// ```
+    /// # #[cfg(feature = "lang-de")] {
+    /// use phonenumber::country;
+    ///
+    /// assert_eq!(Some("Deutschland"), country::DE.name("de"));
+    /// # }
+    /// ```
+    pub fn name(&self, language: &str) -> Option<&'static str> {
+        prefix::candidates(language)
+            .iter()
+            .find_map(|language| LOCALIZED.get(language.as_str())?.get(self.as_ref()))
+            .copied()
+    }
+
+    /// The ISO 3166-1 alpha-3 code, e.g. `DEU`.
+    pub fn alpha3(&self) -> Option<&'static str> {
+        self.iso().map(|(alpha3, _)| alpha3)
+    }
+
+    /// The ISO 3166-1 numeric code, e.g. `276`.
+    pub fn numeric(&self) -> Option<u16> {
+        self.iso().map(|(_, numeric)| numeric)
+    }
+
+    /// The country calling code in the given `Database`.
+    pub fn calling_code(&self, database: &Database) -> Option<u16> {
+        database
+            .by_id(self.as_ref())
+            .map(|meta| meta.country_code())
+    }
+
+    fn iso(&self) -> Option<(&'static str, u16)> {
+        match self {
+            AD => Some(("AND", 20)),
+            AE => Some(("ARE", 784)),
+            AF => Some(("AFG", 4)),
+            AG => Some(("ATG", 28)),
+            AI => Some(("AIA", 660)),
+            AL => Some(("ALB", 8)),
+            AM => Some(("ARM", 51)),
+            AO => Some(("AGO", 24)),
+            AR => Some(("ARG", 32)),
+            AS => Some(("ASM", 16)),
+            AT => Some(("AUT", 40)),
+            AU => Some(("AUS", 36)),
+            AW => Some(("ABW", 533)),
+            AX => Some(("ALA", 248)),
+            AZ => Some(("AZE", 31)),
+            BA => Some(("BIH", 70)),
+            BB => Some(("BRB", 52)),
+            BD => Some(("BGD", 50)),
+            BE => Some(("BEL", 56)),
+            BF => Some(("BFA", 854)),
+            BG => Some(("BGR", 100)),
+            BH => Some(("BHR", 48)),
+            BI => Some(("BDI", 108)),
+            BJ => Some(("BEN", 204)),
+            BL => Some(("BLM", 652)),
+            BM => Some(("BMU", 60)),
+            BN => Some(("BRN", 96)),
+            BO => Some(("BOL", 68)),
+            BQ => Some(("BES", 535)),
+            BR => Some(("BRA", 76)),
+            BS => Some(("BHS", 44)),
+            BT => Some(("BTN", 64)),
+            BW => Some(("BWA", 72)),
+            BY => Some(("BLR", 112)),
+            BZ => Some(("BLZ", 84)),
+            CA => Some(("CAN", 124)),
+            CC => Some(("CCK", 166)),
+            CD => Some(("COD", 180)),
+            CF => Some(("CAF", 140)),
+            CG => Some(("COG", 178)),
+            CH => Some(("CHE", 756)),
+            CI => Some(("CIV", 384)),
+            CK => Some(("COK", 184)),
+            CL => Some(("CHL", 152)),
+            CM => Some(("CMR", 120)),
+            CN => Some(("CHN", 156)),
+            CO => Some(("COL", 170)),
+            CR => Some(("CRI", 188)),
+            CU => Some(("CUB", 192)),
+            CV => Some(("CPV", 132)),
+            CW => Some(("CUW", 531)),
+            CX => Some(("CXR", 162)),
+            CY => Some(("CYP", 196)),
+            CZ => Some(("CZE", 203)),
+            DE => Some(("DEU", 276)),
+            DJ => Some(("DJI", 262)),
+            DK => Some(("DNK", 208)),
+            DM => Some(("DMA", 212)),
+            DO => Some(("DOM", 214)),
+            DZ => Some(("DZA", 12)),
+            EC => Some(("ECU", 218)),
+            EE => Some(("EST", 233)),
+            EG => Some(("EGY", 818)),
+            EH => Some(("ESH", 732)),
+            ER => Some(("ERI", 232)),
+            ES => Some(("ESP", 724)),
+            ET => Some(("ETH", 231)),
+            FI => Some(("FIN", 246)),
+            FJ => Some(("FJI", 242)),
+            FK => Some(("FLK", 238)),
+            FM => Some(("FSM", 583)),
+            FO => Some(("FRO", 234)),
+            FR => Some(("FRA", 250)),
+            GA => Some(("GAB", 266)),
+            GB => Some(("GBR", 826)),
+            GD => Some(("GRD", 308)),
+            GE => Some(("GEO", 268)),
+            GF => Some(("GUF", 254)),
+            GG => Some(("GGY", 831)),
+            GH => Some(("GHA", 288)),
+            GI => Some(("GIB", 292)),
+            GL => Some(("GRL", 304)),
+            GM => Some(("GMB", 270)),
+            GN => Some(("GIN", 324)),
+            GP => Some(("GLP", 312)),
+            GQ => Some(("GNQ", 226)),
+            GR => Some(("GRC", 300)),
+            GT => Some(("GTM", 320)),
+            GU => Some(("GUM", 316)),
+            GW => Some(("GNB", 624)),
+            GY => Some(("GUY", 328)),
+            HK => Some(("HKG", 344)),
+            HN => Some(("HND", 340)),
+            HR => Some(("HRV", 191)),
+            HT => Some(("HTI", 332)),
+            HU => Some(("HUN", 348)),
+            ID => Some(("IDN", 360)),
+            IE => Some(("IRL", 372)),
+            IL => Some(("ISR", 376)),
+            IM => Some(("IMN", 833)),
+            IN => Some(("IND", 356)),
+            IO => Some(("IOT", 86)),
+            IQ => Some(("IRQ", 368)),
+            IR => Some(("IRN", 364)),
+            IS => Some(("ISL", 352)),
+            IT => Some(("ITA", 380)),
+            JE => Some(("JEY", 832)),
+            JM => Some(("JAM", 388)),
+            JO => Some(("JOR", 400)),
+            JP => Some(("JPN", 392)),
+            KE => Some(("KEN", 404)),
+            KG => Some(("KGZ", 417)),
+            KH => Some(("KHM", 116)),
+            KI => Some(("KIR", 296)),
+            KM => Some(("COM", 174)),
+            KN => Some(("KNA", 659)),
+            KP => Some(("PRK", 408)),
+            KR => Some(("KOR", 410)),
+            KW => Some(("KWT", 414)),
+            KY => Some(("CYM", 136)),
+            KZ => Some(("KAZ", 398)),
+            LA => Some(("LAO", 418)),
+            LB => Some(("LBN", 422)),
+            LC => Some(("LCA", 662)),
+            LI => Some(("LIE", 438)),
+            LK => Some(("LKA", 144)),
+            LR => Some(("LBR", 430)),
+            LS => Some(("LSO", 426)),
+            LT => Some(("LTU", 440)),
+            LU => Some(("LUX", 442)),
+            LV => Some(("LVA", 428)),
+            LY => Some(("LBY", 434)),
+            MA => Some(("MAR", 504)),
+            MC => Some(("MCO", 492)),
+            MD => Some(("MDA", 498)),
+            ME => Some(("MNE", 499)),
+            MF => Some(("MAF", 663)),
+            MG => Some(("MDG", 450)),
+            MH => Some(("MHL", 584)),
+            MK => Some(("MKD", 807)),
+            ML => Some(("MLI", 466)),
+            MM => Some(("MMR", 104)),
+            MN => Some(("MNG", 496)),
+            MO => Some(("MAC", 446)),
+            MP => Some(("MNP", 580)),
+            MQ => Some(("MTQ", 474)),
+            MR => Some(("MRT", 478)),
+            MS => Some(("MSR", 500)),
+            MT => Some(("MLT", 470)),
+            MU => Some(("MUS", 480)),
+            MV => Some(("MDV", 462)),
+            MW => Some(("MWI", 454)),
+            MX => Some(("MEX", 484)),
+            MY => Some(("MYS", 458)),
+            MZ => Some(("MOZ", 508)),
+            NA => Some(("NAM", 516)),
+            NC => Some(("NCL", 540)),
+            NE => Some(("NER", 562)),
+            NF => Some(("NFK", 574)),
+            NG => Some(("NGA", 566)),
+            NI => Some(("NIC", 558)),
+            NL => Some(("NLD", 528)),
+            NO => Some(("NOR", 578)),
+            NP => Some(("NPL", 524)),
+            NR => Some(("NRU", 520)),
+            NU => Some(("NIU", 570)),
+            NZ => Some(("NZL", 554)),
+            OM => Some(("OMN", 512)),
+            PA => Some(("PAN", 591)),
+            PE => Some(("PER", 604)),
+            PF => Some(("PYF", 258)),
+            PG => Some(("PNG", 598)),
+            PH => Some(("PHL", 608)),
+            PK => Some(("PAK", 586)),
+            PL => Some(("POL", 616)),
+            PM => Some(("SPM", 666)),
+            PR => Some(("PRI", 630)),
+            PS => Some(("PSE", 275)),
+            PT => Some(("PRT", 620)),
+            PW => Some(("PLW", 585)),
+            PY => Some(("PRY", 600)),
+            QA => Some(("QAT", 634)),
+            RE => Some(("REU", 638)),
+            RO => Some(("ROU", 642)),
+            RS => Some(("SRB", 688)),
+            RU => Some(("RUS", 643)),
+            RW => Some(("RWA", 646)),
+            SA => Some(("SAU", 682)),
+            SB => Some(("SLB", 90)),
+            SC => Some(("SYC", 690)),
+            SD => Some(("SDN", 729)),
+            SE => Some(("SWE", 752)),
+            SG => Some(("SGP", 702)),
+            SH => Some(("SHN", 654)),
+            SI => Some(("SVN", 705)),
+            SJ => Some(("SJM", 744)),
+            SK => Some(("SVK", 703)),
+            SL => Some(("SLE", 694)),
+            SM => Some(("SMR", 674)),
+            SN => Some(("SEN", 686)),
+            SO => Some(("SOM", 706)),
+            SR => Some(("SUR", 740)),
+            SS => Some(("SSD", 728)),
+            ST => Some(("STP", 678)),
+            SV => Some(("SLV", 222)),
+            SX => Some(("SXM", 534)),
+            SY => Some(("SYR", 760)),
+            SZ => Some(("SWZ", 748)),
+            TC => Some(("TCA", 796)),
+            TD => Some(("TCD", 148)),
+            TG => Some(("TGO", 768)),
+            TH => Some(("THA", 764)),
+            TJ => Some(("TJK", 762)),
+            TK => Some(("TKL", 772)),
+            TL => Some(("TLS", 626)),
+            TM => Some(("TKM", 795)),
+            TN => Some(("TUN", 788)),
+            TO => Some(("TON", 776)),
+            TR => Some(("TUR", 792)),
+            TT => Some(("TTO", 780)),
+            TV => Some(("TUV", 798)),
+            TW => Some(("TWN", 158)),
+            TZ => Some(("TZA", 834)),
+            UA => Some(("UKR", 804)),
+            UG => Some(("UGA", 800)),
+            US => Some(("USA", 840)),
+            UY => Some(("URY", 858)),
+            UZ => Some(("UZB", 860)),
+            VA => Some(("VAT", 336)),
+            VC => Some(("VCT", 670)),
+            VE => Some(("VEN", 862)),
+            VG => Some(("VGB", 92)),
+            VI => Some(("VIR", 850)),
+            VN => Some(("VNM", 704)),
+            VU => Some(("VUT", 548)),
+            WF => Some(("WLF", 876)),
+            WS => Some(("WSM", 882)),
+            YE => Some(("YEM", 887)),
+            YT => Some(("MYT", 175)),
+            ZA => Some(("ZAF", 710)),
+            ZM => Some(("ZMB", 894)),
+            ZW => Some(("ZWE", 716)),
+
+            // Not assigned in ISO 3166-1.
+            AC | TA | XK => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::country::{self, Id};
+    use crate::metadata::DEFAULT as DATABASE;
+
+    #[test]
+    fn iter() {
+        assert_eq!(245, Id::iter().count());
+
+        // A build with `PHONENUMBER_REGIONS` only embeds some of them.
+        if option_env!("PHONENUMBER_REGIONS").is_none() {
+            assert!(Id::iter().all(|id| DATABASE.by_id(id.as_ref()).is_some()));
+        }
+    }
+
+    #[test]
+    fn iso() {
+        assert_eq!(Some("DEU"), country::DE.alpha3());
+        assert_eq!(Some(276), country::DE.numeric());
+        assert_eq!(Some(4), country::AF.numeric());
+        assert_eq!(None, country::XK.alpha3());
+    }
+
+    #[test]
+    fn calling_code() {
+        assert_eq!(Some(49), country::DE.calling_code(&DATABASE));
+        assert_eq!(Some(1), country::CA.calling_code(&DATABASE));
+        assert_eq!(Some(7), country::KZ.calling_code(&DATABASE));
+    }
+
+    #[cfg(all(feature = "lang-en", feature = "lang-zh_Hant", feature = "lang-iw"))]
+    #[test]
+    fn name() {
+        assert_eq!(Some("United States"), country::US.name("en"));
+        assert_eq!(Some("美國"), country::US.name("zh-TW"));
+        assert_eq!(Some("ארצות הברית"), country::US.name("he"));
+        assert_eq!(None, country::US.name("nb"));
+        assert!(Id::iter().all(|id| id.name("en").is_some()));
+    }
+}
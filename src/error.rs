@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
+
 use thiserror::Error;
 
 /// Metadata loading errors.
@@ -30,6 +32,15 @@ pub enum Metadata {
     #[allow(unused)] // This is unused in the build script
     MissingValue { phase: String, name: String },
 
+    /// A value was not valid.
+    #[error("{phase}: invalid value for {name:?}: {value:?}")]
+    #[allow(unused)] // This is unused in the build script
+    InvalidValue {
+        phase: String,
+        name: String,
+        value: String,
+    },
+
     /// An element was not handled.
     #[error("{phase}: unhandled element: {name:?}")]
     UnhandledElement { phase: String, name: String },
@@ -49,6 +60,7 @@ pub enum Metadata {
 
 /// Parsing errors.
 #[derive(Error, Clone, Debug)]
+#[allow(unused)] // This is unused in the build script
 pub enum Parse {
     /// This generally indicates the string passed in had less than 3 digits in
     /// it.
@@ -87,33 +99,221 @@ pub enum Parse {
 }
 
 /// Loading of Database) Error
+///
+/// Every error carries the `Location` in the metadata it happened at, as far
+/// as it is known, see `LoadMetadata::location`.
 #[derive(Error, Debug)]
 pub enum LoadMetadata {
     /// Parsing XML failed, the XML is malformed.
-    #[error("Malformed Metadata XML: {0}")]
-    Xml(#[from] xml::Error),
+    #[error("{}Malformed Metadata XML: {0}", .1.prefix())]
+    Xml(#[source] xml::Error, Box<Location>),
 
     /// Parsing UTF-8 string from XML failed.
-    #[error("Non UTF-8 string in Metadata XML: {0}")]
-    Utf8(#[from] std::str::Utf8Error),
+    #[error("{}Non UTF-8 string in Metadata XML: {0}", .1.prefix())]
+    Utf8(#[source] std::str::Utf8Error, Box<Location>),
 
     /// Metadata Error
-    #[error("{0}")]
-    Metadata(#[from] Metadata),
+    #[error("{}{0}", .1.prefix())]
+    Metadata(#[source] Metadata, Box<Location>),
 
     /// Malformed integer in Metadata XML database
-    #[error("Malformed integer in Metadata XML: {0}")]
-    Integer(#[from] std::num::ParseIntError),
+    #[error("{}Malformed integer in Metadata XML: {0}", .1.prefix())]
+    Integer(#[source] std::num::ParseIntError, Box<Location>),
 
     /// Malformed boolean in Metadata XML database
-    #[error("Malformed boolean in Metadata XML: {0}")]
-    Bool(#[from] std::str::ParseBoolError),
+    #[error("{}Malformed boolean in Metadata XML: {0}", .1.prefix())]
+    Bool(#[source] std::str::ParseBoolError, Box<Location>),
 
     /// I/O-Error while reading Metadata XML database
-    #[error("I/O-Error in Metadata XML: {0}")]
-    Io(#[from] std::io::Error),
+    #[error("{}I/O-Error in Metadata XML: {0}", .1.prefix())]
+    Io(#[source] std::io::Error, Box<Location>),
 
     /// Malformed Regex in Metadata XML database
-    #[error("Malformed Regex: {0}")]
-    Regex(#[from] regex::Error),
+    #[error("{}Malformed Regex: {0}", .1.prefix())]
+    Regex(#[source] regex::Error, Box<Location>),
+
+    /// Malformed metadata in a format other than XML.
+    #[error("{}Malformed {format}: {message}", .location.prefix())]
+    #[allow(unused)] // This is unused in the build script
+    Malformed {
+        format: &'static str,
+        message: String,
+        location: Box<Location>,
+    },
+}
+
+macro_rules! from {
+    ($($variant:ident($source:ty)),* $(,)?) => {
+        $(
+            impl From<$source> for LoadMetadata {
+                fn from(error: $source) -> Self {
+                    LoadMetadata::$variant(error, Box::default())
+                }
+            }
+        )*
+    };
+}
+
+from! {
+    Xml(xml::Error),
+    Utf8(std::str::Utf8Error),
+    Metadata(Metadata),
+    Integer(std::num::ParseIntError),
+    Bool(std::str::ParseBoolError),
+    Io(std::io::Error),
+    Regex(regex::Error),
+}
+
+/// Where in the metadata an error happened, as far as it is known.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Location {
+    /// Byte offset in the XML.
+    pub offset: Option<usize>,
+
+    /// Line in the XML, starting at 1.
+    pub line: Option<usize>,
+
+    /// Column in the XML in bytes, starting at 1.
+    pub column: Option<usize>,
+
+    /// ID of the territory.
+    pub territory: Option<String>,
+
+    /// Path to the element or attribute within the territory, e.g.
+    /// `mobile/nationalNumberPattern`.
+    pub field: Option<String>,
+}
+
+impl Location {
+    /// The location of the given byte offset in the XML.
+    pub(crate) fn at(offset: usize, content: &[u8]) -> Self {
+        let offset = offset.min(content.len());
+        let before = &content[..offset];
+        let start = before
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        Location {
+            offset: Some(offset),
+            line: Some(before.iter().filter(|&&c| c == b'\n').count() + 1),
+            column: Some(offset - start + 1),
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = Vec::new();
+
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            parts.push(format!("{}:{}", line, column));
+        } else if let Some(offset) = self.offset {
+            parts.push(format!("byte {}", offset));
+        }
+
+        if let Some(territory) = &self.territory {
+            parts.push(format!("territory {}", territory));
+        }
+
+        if let Some(field) = &self.field {
+            parts.push(field.clone());
+        }
+
+        f.write_str(&parts.join(", "))
+    }
+}
+
+impl Location {
+    /// The location followed by a colon, or nothing if it is unknown.
+    fn prefix(&self) -> String {
+        if *self == Location::default() {
+            String::new()
+        } else {
+            format!("{}: ", self)
+        }
+    }
+}
+
+#[allow(unused)] // This is unused in the build script
+impl LoadMetadata {
+    /// Malformed metadata in a format other than XML.
+    pub(crate) fn malformed<S: Into<String>>(format: &'static str, message: S) -> Self {
+        LoadMetadata::Malformed {
+            format,
+            message: message.into(),
+            location: Box::default(),
+        }
+    }
+
+    /// Where the error happened, if known.
+    pub fn location(&self) -> Option<&Location> {
+        let location = match self {
+            LoadMetadata::Xml(_, location)
+            | LoadMetadata::Utf8(_, location)
+            | LoadMetadata::Metadata(_, location)
+            | LoadMetadata::Integer(_, location)
+            | LoadMetadata::Bool(_, location)
+            | LoadMetadata::Io(_, location)
+            | LoadMetadata::Regex(_, location)
+            | LoadMetadata::Malformed { location, .. } => location,
+        };
+
+        Some(&**location).filter(|location| **location != Location::default())
+    }
+
+    fn locate<F: FnOnce(&mut Location)>(mut self, f: F) -> Self {
+        match &mut self {
+            LoadMetadata::Xml(_, location)
+            | LoadMetadata::Utf8(_, location)
+            | LoadMetadata::Metadata(_, location)
+            | LoadMetadata::Integer(_, location)
+            | LoadMetadata::Bool(_, location)
+            | LoadMetadata::Io(_, location)
+            | LoadMetadata::Regex(_, location)
+            | LoadMetadata::Malformed { location, .. } => f(location),
+        }
+
+        self
+    }
+
+    /// Record the byte offset of the error in the given XML, along with its
+    /// line and column.
+    pub(crate) fn at_offset(self, offset: usize, content: &[u8]) -> Self {
+        self.locate(|location| {
+            if location.offset.is_none() {
+                let at = Location::at(offset, content);
+
+                location.offset = at.offset;
+                location.line = at.line;
+                location.column = at.column;
+            }
+        })
+    }
+
+    /// Record the territory the error happened in.
+    pub(crate) fn in_territory<S: Into<String>>(self, id: Option<S>) -> Self {
+        match id {
+            Some(id) => self.locate(|location| {
+                location.territory.get_or_insert_with(|| id.into());
+            }),
+
+            None => self,
+        }
+    }
+
+    /// Record the element or attribute the error happened in, for nested
+    /// elements from the innermost outwards.
+    pub(crate) fn in_field<S: AsRef<[u8]>>(self, name: S) -> Self {
+        let name = String::from_utf8_lossy(name.as_ref()).into_owned();
+
+        self.locate(|location| {
+            location.field = Some(match location.field.take() {
+                Some(field) => format!("{}/{}", name, field),
+                None => name,
+            });
+        })
+    }
 }
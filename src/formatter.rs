@@ -14,7 +14,7 @@
 
 use crate::{
     consts,
-    metadata::{Database, Format, Metadata, DATABASE},
+    metadata::{Database, Format, Metadata},
     phone_number::PhoneNumber,
 };
 use std::{borrow::Cow, fmt};
@@ -99,7 +99,14 @@ pub fn format_with<'d, 'n>(
 
 impl<'n, 'd, 'f> fmt::Display for Formatter<'n, 'd, 'f> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let db = self.database.unwrap_or(&DATABASE);
+        let current;
+        let db = match self.database {
+            Some(db) => db,
+            None => {
+                current = Database::snapshot();
+                &current
+            }
+        };
 
         // If the country code is invalid, return an error.
         let meta = db
@@ -205,22 +212,13 @@ impl<'n, 'd, 'f> fmt::Display for Formatter<'n, 'd, 'f> {
     }
 }
 
-fn formatter<'a>(number: &str, formats: &'a [Format]) -> Option<&'a Format> {
+pub(crate) fn formatter<'a>(number: &str, formats: &'a [Format]) -> Option<&'a Format> {
     for format in formats {
         let leading = format.leading_digits();
 
         if leading.is_empty()
-            || leading
-                .last()
-                .unwrap()
-                .find(number)
-                .map(|m| m.start() == 0)
-                .unwrap_or(false)
-                && format
-                    .pattern()
-                    .find(number)
-                    .map(|m| m.start() == 0 && m.end() == number.len())
-                    .unwrap_or(false)
+            || leading.last().unwrap().match_at_start(number).is_some()
+                && format.pattern().match_at_start(number) == Some(number.len())
         {
             return Some(format);
         }
@@ -236,27 +234,27 @@ fn replace(
     transform: Option<&str>,
     carrier: Option<&str>,
 ) -> String {
-    formatter
-        .pattern()
-        .replace(
-            national,
-            &*if let Some(transform) = transform {
-                let first = consts::FIRST_GROUP
-                    .captures(formatter.format())
-                    .unwrap()
-                    .get(1)
-                    .unwrap()
-                    .as_str();
-                let format = transform.replace(*consts::NP, meta.national_prefix().unwrap_or(""));
-                let format = format.replace(*consts::FG, &format!("${}", first));
-                let format = format.replace(*consts::CC, carrier.unwrap_or(""));
-
-                consts::FIRST_GROUP.replace(formatter.format(), &*format)
-            } else {
-                Cow::Borrowed(formatter.format())
-            },
-        )
-        .into()
+    let captures = if let Some(captures) = formatter.pattern().captures_at_start(national) {
+        captures
+    } else {
+        return national.to_owned();
+    };
+
+    captures.replace(&if let Some(transform) = transform {
+        let first = consts::FIRST_GROUP
+            .captures(formatter.format())
+            .unwrap()
+            .get(1)
+            .unwrap()
+            .as_str();
+        let format = transform.replace(*consts::NP, meta.national_prefix().unwrap_or(""));
+        let format = format.replace(*consts::FG, &format!("${}", first));
+        let format = format.replace(*consts::CC, carrier.unwrap_or(""));
+
+        consts::FIRST_GROUP.replace(formatter.format(), &*format)
+    } else {
+        Cow::Borrowed(formatter.format())
+    })
 }
 
 #[cfg(test)]
@@ -0,0 +1,164 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::country;
+use crate::metadata::Database;
+use crate::phone_number::{PhoneNumber, Type};
+use crate::prefix::{self, Prefixes};
+use crate::validator;
+
+const GEOCODING: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/geocoding.bin"));
+
+lazy_static! {
+    static ref PREFIXES: Prefixes = Prefixes::embedded(GEOCODING);
+}
+
+/// Get a description of the geographical area of the phone number in the
+/// given language, e.g. `en`, `de-CH` or `zh-TW`.
+///
+/// Only valid numbers can be described. Numbers that are not geographical, or
+/// whose area is not known, are described by the name of their country, see
+/// `country::Id::name`. Descriptions missing in the given language are taken
+/// from English, except for the areas in Chinese, Japanese and Korean, as long
+/// as the `lang-en` feature is enabled.
+///
+/// ```
+/// # #[cfg(feature = "lang-en")] {
+/// let number = phonenumber::parse(None, "+1 201 555 0123").unwrap();
+///
+/// assert_eq!(
+///     Some("New Jersey"),
+///     phonenumber::geocoding::description(&number, "en")
+/// );
+/// # }
+/// ```
+pub fn description(number: &PhoneNumber, language: &str) -> Option<&'static str> {
+    description_with(&Database::snapshot(), number, language)
+}
+
+/// Get a description of the geographical area of the phone number in the
+/// given language, with the given `Database`.
+pub fn description_with(
+    database: &Database,
+    number: &PhoneNumber,
+    language: &str,
+) -> Option<&'static str> {
+    let code = number.code().value();
+    let kind = number.number_type(database);
+
+    if kind == Type::Unknown {
+        return None;
+    }
+
+    if validator::is_geographical(kind, code) {
+        let digits = format!("{}{}", code, number.national());
+        let mut candidates = prefix::candidates(language);
+
+        if !matches!(
+            candidates.last().map(String::as_str),
+            Some("zh" | "ja" | "ko")
+        ) {
+            candidates.push("en".into());
+        }
+
+        let area = candidates
+            .iter()
+            .find_map(|language| PREFIXES.get(language, code, &digits));
+
+        if area.is_some() {
+            return area;
+        }
+    }
+
+    country_name(database, number, language)
+}
+
+/// The name of the country of the phone number, unless it is valid in several
+/// countries sharing its country code.
+fn country_name(database: &Database, number: &PhoneNumber, language: &str) -> Option<&'static str> {
+    let national = number.national().to_string();
+    let regions = database.region(&number.code().value())?;
+
+    let mut valid = regions.iter().filter(|&&region| {
+        regions.len() == 1
+            || database
+                .by_id(region)
+                .is_some_and(|meta| validator::number_type(meta, &national) != Type::Unknown)
+    });
+
+    let id = match (valid.next(), valid.next()) {
+        (Some(id), None) => id.parse::<country::Id>().ok()?,
+        _ => return None,
+    };
+
+    id.name(language).or_else(|| id.name("en"))
+}
+
+/// The languages with embedded descriptions, as enabled with the `lang-*`
+/// features.
+pub fn languages() -> impl Iterator<Item = &'static str> {
+    PREFIXES.languages()
+}
+
+#[cfg(all(
+    test,
+    feature = "lang-en",
+    feature = "lang-de",
+    feature = "lang-fr",
+    feature = "lang-it",
+    feature = "lang-ja"
+))]
+mod test {
+    use crate::country;
+    use crate::geocoding::description;
+    use crate::parser;
+
+    #[test]
+    fn description_in_language() {
+        let number = parser::parse(Some(country::DE), "089 1234567").unwrap();
+        assert_eq!(Some("München"), description(&number, "de"));
+        assert_eq!(Some("Munich"), description(&number, "en"));
+        assert_eq!(Some("München"), description(&number, "de-AT"));
+
+        let number = parser::parse(Some(country::IT), "06 1234 5678").unwrap();
+        assert_eq!(Some("Roma"), description(&number, "it"));
+    }
+
+    #[test]
+    fn english_fallback() {
+        let number = parser::parse(None, "+1 201 555 0123").unwrap();
+        assert_eq!(Some("New Jersey"), description(&number, "de"));
+        assert_eq!(Some("アメリカ合衆国"), description(&number, "ja"));
+    }
+
+    #[test]
+    fn not_geographical() {
+        let number = parser::parse(None, "+1 800 253 0000").unwrap();
+        assert_eq!(None, description(&number, "en"));
+
+        let number = parser::parse(None, "+1 201 555").unwrap();
+        assert_eq!(None, description(&number, "en"));
+    }
+
+    #[test]
+    fn country_name() {
+        let number = parser::parse(Some(country::DE), "0151 23456789").unwrap();
+        assert_eq!(Some("Deutschland"), description(&number, "de"));
+        assert_eq!(Some("Allemagne"), description(&number, "fr-CH"));
+        assert_eq!(Some("Germany"), description(&number, "nb"));
+
+        let number = parser::parse(None, "+1 242 357 1234").unwrap();
+        assert_eq!(Some("Bahamas"), description(&number, "en"));
+    }
+}
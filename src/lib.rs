@@ -20,12 +20,12 @@ extern crate lazy_static;
 extern crate nom;
 extern crate thiserror;
 
-extern crate either;
+extern crate arc_swap;
 extern crate fnv;
 extern crate itertools;
 extern crate quick_xml as xml;
 extern crate regex;
-extern crate regex_cache;
+extern crate regex_syntax;
 
 extern crate serde;
 #[macro_use]
@@ -37,6 +37,7 @@ extern crate bincode;
 extern crate doc_comment;
 
 #[cfg(test)]
+#[allow(clippy::single_component_path_imports)]
 use rstest_reuse;
 
 #[cfg(test)]
@@ -44,7 +45,10 @@ doctest!("../README.md");
 
 /// Errors for various parts of the crate.
 mod error;
-pub use crate::error::{Metadata as MetadataError, Parse as ParseError};
+pub use crate::error::{
+    LoadMetadata as LoadMetadataError, Location as MetadataLocation, Metadata as MetadataError,
+    Parse as ParseError,
+};
 
 /// Phone number metadata, containing patterns, formatting and other useful
 /// data about countries and phone numbers.
@@ -62,17 +66,37 @@ pub use crate::national_number::NationalNumber;
 mod extension;
 pub use crate::extension::Extension;
 
-mod carrier;
+/// Phone number carriers, and their names by number prefix with the
+/// `carrier` feature.
+pub mod carrier;
 pub use crate::carrier::Carrier;
 
+mod prefix;
+
+/// Descriptions of the geographical area of phone numbers.
+#[cfg(feature = "geocoding")]
+pub mod geocoding;
+
+/// Time zones of phone numbers.
+pub mod timezones;
+
 mod phone_number;
 pub use crate::phone_number::{PhoneNumber, Type};
 
 mod parser;
-pub use crate::parser::{parse, parse_with};
+pub use crate::parser::{
+    extract_possible_number, parse, parse_all, parse_all_with, parse_with, parse_with_candidates,
+    parse_with_candidates_with,
+};
 
 mod formatter;
 pub use crate::formatter::{format, format_with, Formatter, Mode};
 
 mod validator;
 pub use crate::validator::{is_valid, is_valid_with, is_viable, Validation};
+
+mod alpha;
+pub use crate::alpha::{alpha_spellings, convert_alpha_characters_in_number, is_alpha_number};
+
+mod normalizer;
+pub use crate::normalizer::{normalize, normalize_diallable_chars_only, normalize_digits_only};
@@ -0,0 +1,664 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use fnv::FnvHashMap;
+use regex::RegexBuilder;
+
+use crate::error;
+use crate::metadata::{database, loader, Database, Metadata};
+use crate::phone_number::Type;
+
+/// Builder for the metadata of a territory, as an alternative to writing it in
+/// XML.
+///
+/// ```
+/// use phonenumber::metadata::{Database, FormatBuilder, MetadataBuilder};
+///
+/// let database = Database::builder()
+///     .region(
+///         MetadataBuilder::new("001", 999)
+///             .general(r"[1-9]\d{5}", &[6])
+///             .mobile(r"[89]\d{5}", &[6])
+///             .example(phonenumber::Type::Mobile, "812345")
+///             .format(FormatBuilder::new(r"(\d{3})(\d{3})", "$1 $2")),
+///     )
+///     .build()
+///     .unwrap();
+///
+/// let number = phonenumber::parse_with(&database, None, "+999 812 345").unwrap();
+/// assert!(phonenumber::is_valid_with(&database, &number));
+/// ```
+#[derive(Clone, Debug)]
+pub struct MetadataBuilder {
+    meta: loader::Metadata,
+    error: Option<error::Metadata>,
+}
+
+/// Builder for a number format, see `MetadataBuilder::format`.
+#[derive(Clone, Debug)]
+pub struct FormatBuilder {
+    format: loader::Format,
+    international: Option<String>,
+}
+
+/// Builder for a database made of `MetadataBuilder`s, see `Database::builder`.
+#[derive(Clone, Default, Debug)]
+pub struct DatabaseBuilder {
+    regions: Vec<MetadataBuilder>,
+    label: Option<String>,
+}
+
+impl MetadataBuilder {
+    /// Start the metadata for the given region ID and its country code.
+    ///
+    /// The ID is usually a known region or "001" for non-geographical
+    /// entities, but any non-empty ID can be used, e.g. for a fictional region
+    /// in tests.
+    pub fn new<S: Into<String>>(id: S, country_code: u16) -> Self {
+        MetadataBuilder {
+            meta: loader::Metadata {
+                id: Some(id.into()),
+                country_code: Some(country_code),
+                ..Default::default()
+            },
+            error: None,
+        }
+    }
+
+    fn descriptor<S: Into<String>>(mut self, kind: Type, pattern: S, lengths: &[u16]) -> Self {
+        let desc = loader::Descriptor {
+            national_number: Some(pattern.into()),
+            possible_length: lengths.to_vec(),
+            ..Default::default()
+        };
+
+        match self.get(kind) {
+            Some(slot) => *slot = Some(desc),
+            None => self.fail(kind, "descriptor"),
+        }
+
+        self
+    }
+
+    /// The descriptor for the given type, where `Unknown` is the general one.
+    fn get(&mut self, kind: Type) -> Option<&mut Option<loader::Descriptor>> {
+        let meta = &mut self.meta;
+
+        Some(match kind {
+            Type::Unknown => &mut meta.general,
+            Type::FixedLine => &mut meta.fixed_line,
+            Type::Mobile => &mut meta.mobile,
+            Type::TollFree => &mut meta.toll_free,
+            Type::PremiumRate => &mut meta.premium_rate,
+            Type::SharedCost => &mut meta.shared_cost,
+            Type::PersonalNumber => &mut meta.personal_number,
+            Type::Voip => &mut meta.voip,
+            Type::Pager => &mut meta.pager,
+            Type::Uan => &mut meta.uan,
+            Type::Emergency => &mut meta.emergency,
+            Type::Voicemail => &mut meta.voicemail,
+            Type::ShortCode => &mut meta.short_code,
+            Type::StandardRate => &mut meta.standard_rate,
+            Type::Carrier => &mut meta.carrier,
+            Type::NoInternational => &mut meta.no_international,
+            Type::FixedLineOrMobile => return None,
+        })
+    }
+
+    /// Keep the first misuse of the builder, to be returned by `build`.
+    fn fail(&mut self, kind: Type, name: &str) {
+        self.error
+            .get_or_insert_with(|| error::Metadata::MissingValue {
+                phase: format!("builder::{:?}", kind),
+                name: name.into(),
+            });
+    }
+
+    /// The general descriptor, which every valid number matches, and the
+    /// possible lengths of all numbers.
+    pub fn general<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::Unknown, pattern, lengths)
+    }
+
+    /// The fixed line descriptor.
+    pub fn fixed_line<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::FixedLine, pattern, lengths)
+    }
+
+    /// The mobile descriptor.
+    pub fn mobile<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::Mobile, pattern, lengths)
+    }
+
+    /// The toll free descriptor.
+    pub fn toll_free<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::TollFree, pattern, lengths)
+    }
+
+    /// The premium rate descriptor.
+    pub fn premium_rate<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::PremiumRate, pattern, lengths)
+    }
+
+    /// The shared cost descriptor.
+    pub fn shared_cost<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::SharedCost, pattern, lengths)
+    }
+
+    /// The personal number descriptor.
+    pub fn personal_number<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::PersonalNumber, pattern, lengths)
+    }
+
+    /// The VoIP descriptor.
+    pub fn voip<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::Voip, pattern, lengths)
+    }
+
+    /// The pager descriptor.
+    pub fn pager<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::Pager, pattern, lengths)
+    }
+
+    /// The UAN descriptor.
+    pub fn uan<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::Uan, pattern, lengths)
+    }
+
+    /// The emergency descriptor.
+    pub fn emergency<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::Emergency, pattern, lengths)
+    }
+
+    /// The voicemail descriptor.
+    pub fn voicemail<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::Voicemail, pattern, lengths)
+    }
+
+    /// The short code descriptor.
+    pub fn short_code<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::ShortCode, pattern, lengths)
+    }
+
+    /// The standard rate descriptor.
+    pub fn standard_rate<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::StandardRate, pattern, lengths)
+    }
+
+    /// The carrier specific descriptor.
+    pub fn carrier<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::Carrier, pattern, lengths)
+    }
+
+    /// The descriptor of numbers that cannot be dialled internationally.
+    pub fn no_international<S: Into<String>>(self, pattern: S, lengths: &[u16]) -> Self {
+        self.descriptor(Type::NoInternational, pattern, lengths)
+    }
+
+    /// The lengths of local only numbers for an already set descriptor, where
+    /// `Unknown` is the general descriptor.
+    pub fn local_lengths(mut self, kind: Type, lengths: &[u16]) -> Self {
+        match self.get(kind).and_then(Option::as_mut) {
+            Some(desc) => desc.possible_local_length = lengths.to_vec(),
+            None => self.fail(kind, "possibleLengths/localOnly"),
+        }
+
+        self
+    }
+
+    /// The example number for an already set descriptor, where `Unknown` is
+    /// the general descriptor.
+    pub fn example<S: Into<String>>(mut self, kind: Type, example: S) -> Self {
+        match self.get(kind).and_then(Option::as_mut) {
+            Some(desc) => desc.example = Some(example.into()),
+            None => self.fail(kind, "exampleNumber"),
+        }
+
+        self
+    }
+
+    /// The pattern of international prefixes, e.g. `00`.
+    pub fn international_prefix<S: Into<String>>(mut self, value: S) -> Self {
+        self.meta.international_prefix = Some(value.into());
+        self
+    }
+
+    /// The international prefix used for formatting when
+    /// `international_prefix` matches several.
+    pub fn preferred_international_prefix<S: Into<String>>(mut self, value: S) -> Self {
+        self.meta.preferred_international_prefix = Some(value.into());
+        self
+    }
+
+    /// The national prefix, e.g. `0`.
+    pub fn national_prefix<S: Into<String>>(mut self, value: S) -> Self {
+        self.meta.national_prefix = Some(value.into());
+        self
+    }
+
+    /// The prefix used when formatting extensions.
+    pub fn preferred_extension_prefix<S: Into<String>>(mut self, value: S) -> Self {
+        self.meta.preferred_extension_prefix = Some(value.into());
+        self
+    }
+
+    /// The pattern of national prefixes stripped when parsing.
+    pub fn national_prefix_for_parsing<S: Into<String>>(mut self, value: S) -> Self {
+        self.meta.national_prefix_for_parsing = Some(value.into());
+        self
+    }
+
+    /// The rule applied to numbers after matching
+    /// `national_prefix_for_parsing`.
+    pub fn national_prefix_transform_rule<S: Into<String>>(mut self, value: S) -> Self {
+        self.meta.national_prefix_transform_rule = Some(value.into());
+        self
+    }
+
+    /// Whether this is the main region for a country code shared by several.
+    pub fn main_country_for_code(mut self, value: bool) -> Self {
+        self.meta.main_country_for_code = value;
+        self
+    }
+
+    /// The pattern of the leading digits of numbers in this region, for
+    /// country codes shared by several.
+    pub fn leading_digits<S: Into<String>>(mut self, value: S) -> Self {
+        self.meta.leading_digits = Some(value.into());
+        self
+    }
+
+    /// Whether mobile numbers can be ported between carriers.
+    pub fn mobile_number_portable(mut self, value: bool) -> Self {
+        self.meta.mobile_number_portable = value;
+        self
+    }
+
+    /// Add a number format, formats are tried in the order they're added.
+    pub fn format(mut self, format: FormatBuilder) -> Self {
+        let international = format.international.map(|value| {
+            let mut international = format.format.clone();
+            international.format = Some(value);
+            international
+        });
+
+        self.meta.formats.push(format.format);
+        self.meta.international_formats.extend(international);
+        self
+    }
+
+    /// Validate the metadata, returning it in its loaded form.
+    pub fn build_loader(self) -> Result<loader::Metadata, error::LoadMetadata> {
+        self.validate()?;
+        Ok(self.meta)
+    }
+
+    /// Validate the metadata.
+    pub fn build(self) -> Result<Metadata, error::LoadMetadata> {
+        let id = self.meta.id.clone();
+        let meta = self.build_loader()?;
+
+        database::metadata(meta).map_err(|e| e.in_territory(id))
+    }
+
+    fn validate(&self) -> Result<(), error::LoadMetadata> {
+        let id = self.meta.id.clone();
+        let invalid = |name: &str, value: String| {
+            error::LoadMetadata::from(error::Metadata::InvalidValue {
+                phase: "builder".into(),
+                name: name.into(),
+                value,
+            })
+            .in_territory(id.clone())
+            .in_field(name)
+        };
+
+        if let Some(error) = &self.error {
+            return Err(error::LoadMetadata::from(error.clone()).in_territory(id.clone()));
+        }
+
+        let meta = &self.meta;
+        let region = meta.id.as_deref().unwrap_or_default();
+
+        if region.is_empty() {
+            return Err(invalid("id", region.into()));
+        }
+
+        match meta.country_code {
+            Some(1..=999) => (),
+            code => return Err(invalid("countryCode", format!("{:?}", code))),
+        }
+
+        let general = match &meta.general {
+            Some(general) => general,
+            None => {
+                return Err(error::LoadMetadata::from(error::Metadata::MissingValue {
+                    phase: "builder".into(),
+                    name: "generalDesc".into(),
+                })
+                .in_territory(id.clone()))
+            }
+        };
+
+        for (name, desc) in descriptors(meta) {
+            let desc = match desc {
+                Some(desc) => desc,
+                None => continue,
+            };
+
+            // Without possible lengths in the general descriptor, any length
+            // is possible.
+            if let Some(length) = desc
+                .possible_length
+                .iter()
+                .chain(&desc.possible_local_length)
+                .find(|l| {
+                    !general.possible_length.is_empty()
+                        && !general.possible_length.contains(l)
+                        && !general.possible_local_length.contains(l)
+                })
+            {
+                return Err(invalid(
+                    &format!("{}/possibleLengths", name),
+                    length.to_string(),
+                ));
+            }
+
+            if let (Some(pattern), Some(example)) = (&desc.national_number, &desc.example) {
+                let matches = RegexBuilder::new(&format!("^(?:{})$", pattern))
+                    .ignore_whitespace(true)
+                    .build()
+                    .map(|r| r.is_match(example))
+                    .map_err(|e| {
+                        error::LoadMetadata::from(e)
+                            .in_territory(id.clone())
+                            .in_field(format!("{}/nationalNumberPattern", name))
+                    })?;
+
+                let length = example.len() as u16;
+
+                if !matches
+                    || !(desc.possible_length.is_empty() || desc.possible_length.contains(&length))
+                {
+                    return Err(invalid(&format!("{}/exampleNumber", name), example.clone()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The descriptors with their XML names.
+fn descriptors(meta: &loader::Metadata) -> [(&'static str, &Option<loader::Descriptor>); 16] {
+    [
+        ("generalDesc", &meta.general),
+        ("fixedLine", &meta.fixed_line),
+        ("mobile", &meta.mobile),
+        ("tollFree", &meta.toll_free),
+        ("premiumRate", &meta.premium_rate),
+        ("sharedCost", &meta.shared_cost),
+        ("personalNumber", &meta.personal_number),
+        ("voip", &meta.voip),
+        ("pager", &meta.pager),
+        ("uan", &meta.uan),
+        ("emergency", &meta.emergency),
+        ("voicemail", &meta.voicemail),
+        ("shortCode", &meta.short_code),
+        ("standardRate", &meta.standard_rate),
+        ("carrierSpecific", &meta.carrier),
+        ("noInternationalDialling", &meta.no_international),
+    ]
+}
+
+impl FormatBuilder {
+    /// Start a format with the pattern matching the whole national number and
+    /// the format using its groups, e.g. `(\d{3})(\d{3})` and `$1 $2`.
+    ///
+    /// International numbers use the same format unless changed with
+    /// `international`.
+    pub fn new<P: Into<String>, F: Into<String>>(pattern: P, format: F) -> Self {
+        let format = format.into();
+
+        FormatBuilder {
+            format: loader::Format {
+                pattern: Some(pattern.into()),
+                format: Some(format.clone()),
+                ..Default::default()
+            },
+            international: Some(format),
+        }
+    }
+
+    /// Add a pattern for the leading digits of numbers using this format.
+    pub fn leading_digits<S: Into<String>>(mut self, value: S) -> Self {
+        self.format.leading_digits.push(value.into());
+        self
+    }
+
+    /// The rule for adding the national prefix, e.g. `0$1`.
+    pub fn national_prefix<S: Into<String>>(mut self, value: S) -> Self {
+        self.format.national_prefix_formatting_rule = Some(value.into());
+        self
+    }
+
+    /// Whether the national prefix can be left out when formatting.
+    pub fn national_prefix_optional(mut self, value: bool) -> Self {
+        self.format.national_prefix_optional_when_formatting = value;
+        self
+    }
+
+    /// The rule for adding the domestic carrier code.
+    pub fn domestic_carrier<S: Into<String>>(mut self, value: S) -> Self {
+        self.format.domestic_carrier = Some(value.into());
+        self
+    }
+
+    /// The format used for international numbers, or `None` if they're not
+    /// formatted with this pattern.
+    pub fn international<S: Into<String>>(mut self, value: Option<S>) -> Self {
+        self.international = value.map(Into::into);
+        self
+    }
+}
+
+impl DatabaseBuilder {
+    /// Add the metadata of a territory.
+    pub fn region(mut self, region: MetadataBuilder) -> Self {
+        self.regions.push(region);
+        self
+    }
+
+    /// The label reported by the version of the database.
+    pub fn label<S: Into<String>>(mut self, label: S) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Validate the metadata of every territory, and that country codes shared
+    /// by several regions have exactly one main region.
+    pub fn build(self) -> Result<Database, error::LoadMetadata> {
+        let mut by_code = FnvHashMap::<u16, Vec<&loader::Metadata>>::default();
+        let regions = self
+            .regions
+            .into_iter()
+            .map(MetadataBuilder::build_loader)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for meta in &regions {
+            by_code
+                .entry(meta.country_code.unwrap_or_default())
+                .or_default()
+                .push(meta);
+        }
+
+        for (code, regions) in &by_code {
+            let mut ids = regions.iter().map(|m| m.id.as_deref()).collect::<Vec<_>>();
+            ids.sort();
+            ids.dedup();
+
+            let main = regions.iter().filter(|m| m.main_country_for_code).count();
+
+            if ids.len() != regions.len() || (regions.len() > 1 && main != 1) {
+                return Err(error::Metadata::InvalidValue {
+                    phase: "builder".into(),
+                    name: "mainCountryForCode".into(),
+                    value: code.to_string(),
+                }
+                .into());
+            }
+        }
+
+        let database = Database::from(regions)?;
+
+        Ok(match self.label {
+            Some(label) => database.with_label(label),
+            None => database,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error;
+    use crate::metadata::{Database, FormatBuilder, MetadataBuilder};
+    use crate::phone_number::Type;
+    use crate::{formatter, parser, validator, Mode};
+
+    fn fictional() -> MetadataBuilder {
+        MetadataBuilder::new("001", 999)
+            .general(r"[1-9]\d{5,6}", &[6, 7])
+            .fixed_line(r"[1-7]\d{5}", &[6])
+            .mobile(r"[89]\d{6}", &[7])
+            .example(Type::Mobile, "8123456")
+            .format(FormatBuilder::new(r"(\d{3})(\d{3})", "$1 $2").leading_digits("[1-7]"))
+            .format(
+                FormatBuilder::new(r"(\d)(\d{3})(\d{3})", "$1 $2 $3")
+                    .leading_digits("[89]")
+                    .international(Some("$1-$2-$3")),
+            )
+    }
+
+    #[test]
+    fn metadata() {
+        let meta = fictional().build().unwrap();
+
+        assert_eq!(999, meta.country_code());
+        assert_eq!(2, meta.formats().len());
+        assert_eq!("$1-$2-$3", meta.international_formats()[1].format());
+        assert_eq!(
+            Some("8123456"),
+            meta.descriptors().mobile().and_then(|d| d.example())
+        );
+    }
+
+    #[test]
+    fn database() {
+        let database = Database::builder()
+            .region(fictional())
+            .label("fictional")
+            .build()
+            .unwrap();
+
+        assert_eq!(Some("fictional"), database.version().label());
+
+        let number = parser::parse_with(&database, None, "+999 8123456").unwrap();
+        assert!(validator::is_valid_with(&database, &number));
+        assert_eq!(Type::Mobile, number.number_type(&database));
+        assert_eq!(
+            "+999 8-123-456",
+            formatter::format_with(&database, &number)
+                .mode(Mode::International)
+                .to_string()
+        );
+
+        let number = parser::parse_with(&database, None, "+999 123456").unwrap();
+        assert_eq!(Type::FixedLine, number.number_type(&database));
+    }
+
+    #[test]
+    fn fictional_region() {
+        let database = Database::builder()
+            .region(
+                MetadataBuilder::new("XX", 999)
+                    .general(r"[1-9]\d{5}", &[])
+                    .mobile(r"[89]\d{5}", &[6])
+                    .example(Type::Mobile, "812345")
+                    .format(FormatBuilder::new(r"(\d{3})(\d{3})", "$1 $2")),
+            )
+            .build()
+            .unwrap();
+
+        let number = parser::parse_with(&database, None, "+999812345").unwrap();
+        assert!(validator::is_valid_with(&database, &number));
+        assert_eq!(Type::Mobile, number.number_type(&database));
+        assert_eq!(Some("XX"), number.metadata(&database).map(|m| m.id()));
+        assert_eq!(
+            "+999 812 345",
+            formatter::format_with(&database, &number)
+                .mode(Mode::International)
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn validation() {
+        fn invalid(builder: MetadataBuilder) -> error::LoadMetadata {
+            builder.build().unwrap_err()
+        }
+
+        let error = invalid(MetadataBuilder::new("", 999).general(r"\d{6}", &[6]));
+        assert_eq!(
+            Some("id"),
+            error.location().and_then(|l| l.field.as_deref())
+        );
+
+        let error = invalid(MetadataBuilder::new("001", 999));
+        assert!(matches!(
+            error,
+            error::LoadMetadata::Metadata(error::Metadata::MissingValue { .. }, _)
+        ));
+
+        let error = invalid(fictional().example(Type::TollFree, "800123"));
+        assert!(matches!(
+            error,
+            error::LoadMetadata::Metadata(error::Metadata::MissingValue { .. }, _)
+        ));
+
+        let error = invalid(fictional().mobile(r"[89]\d{7}", &[8]));
+        assert_eq!(
+            Some("mobile/possibleLengths"),
+            error.location().and_then(|l| l.field.as_deref())
+        );
+
+        let error = invalid(fictional().example(Type::Mobile, "7123456"));
+        assert_eq!(
+            Some("mobile/exampleNumber"),
+            error.location().and_then(|l| l.field.as_deref())
+        );
+
+        let error = invalid(fictional().fixed_line(r"[1-7]\d{5", &[6]));
+        assert!(matches!(error, error::LoadMetadata::Regex(..)));
+
+        let error = Database::builder()
+            .region(MetadataBuilder::new("GB", 44).general(r"\d{10}", &[10]))
+            .region(MetadataBuilder::new("GG", 44).general(r"\d{10}", &[10]))
+            .build()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            error::LoadMetadata::Metadata(error::Metadata::InvalidValue { .. }, _)
+        ));
+    }
+}
@@ -12,37 +12,184 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
+use std::fmt;
 use std::fs::File;
 use std::hash::Hash;
-use std::io::{BufReader, Cursor};
+use std::io::{self, BufReader, Cursor, Write};
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, OnceLock};
 
+use arc_swap::{ArcSwap, Guard};
 use bincode;
 use bincode::Options;
 use fnv::FnvHashMap;
-use regex_cache::{CachedRegex, CachedRegexBuilder, RegexCache};
 
 use crate::error;
-use crate::metadata::loader;
+#[cfg(feature = "compiled-patterns")]
+use crate::metadata::program::Program;
+use crate::metadata::{
+    diff, export, loader, verify, DatabaseBuilder, LazyRegex, MetadataDiff, MetadataIssue,
+    Reclassified, Version,
+};
+use crate::phone_number::PhoneNumber;
 
 const DATABASE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/database.bin"));
 
 lazy_static! {
-    /// The Google provided metadata database, used as default.
-    pub static ref DEFAULT: Database =
-        Database::from(bincode::options()
-        .with_varint_encoding().deserialize(DATABASE).unwrap()).unwrap();
+    /// The Google provided metadata database, used as default unless replaced
+    /// with `Database::set_current`.
+    pub static ref DEFAULT: Database = Database::embedded(DATABASE);
+
+    /// The database used by the functions without an explicit database.
+    ///
+    /// Reading it takes no lock and does not touch a shared reference count,
+    /// so it does not get in the way of threads using it concurrently.
+    static ref CURRENT: ArcSwap<Database> = ArcSwap::from_pointee(DEFAULT.clone());
 }
 
 /// Representation of a database of metadata for phone number.
+///
+/// Every pattern is owned by the metadata it belongs to and compiled on first
+/// use, after which matching needs no locking, so a `Database` can be shared
+/// freely between threads.
+///
+/// The metadata of the embedded database is decoded per region the first time
+/// the region is looked up. Its strings and patterns are borrowed from the
+/// read-only data of the binary, and its patterns are not checked again since
+/// the build script already did.
 #[derive(Clone, Debug)]
 pub struct Database {
-    cache: Arc<Mutex<RegexCache>>,
-    by_id: FnvHashMap<String, Arc<super::Metadata>>,
-    by_code: FnvHashMap<u16, Vec<Arc<super::Metadata>>>,
+    by_id: FnvHashMap<String, Arc<Region>>,
+    by_code: FnvHashMap<u16, Vec<Arc<Region>>>,
     regions: FnvHashMap<u16, Vec<String>>,
+    version: Version,
+}
+
+/// The metadata for a region, possibly still encoded.
+struct Region {
+    encoded: Option<Encoded<'static>>,
+    metadata: OnceLock<super::Metadata>,
+}
+
+/// An encoded region in the embedded database, as written by the build script.
+#[derive(Deserialize)]
+struct Encoded<'a> {
+    id: String,
+    code: u16,
+    main: bool,
+    #[serde(borrow)]
+    metadata: &'a [u8],
+    #[cfg_attr(not(feature = "compiled-patterns"), allow(unused))]
+    #[serde(borrow)]
+    patterns: Vec<Pattern<'a>>,
+}
+
+/// A pattern of the region compiled by the build script, only present when
+/// built with `compiled-patterns`.
+#[derive(Deserialize)]
+#[cfg_attr(not(feature = "compiled-patterns"), allow(unused))]
+struct Pattern<'a> {
+    source: &'a str,
+    /// The `Program` encoded like the metadata.
+    program: &'a [u8],
+}
+
+impl Region {
+    fn get(&self) -> &super::Metadata {
+        self.metadata.get_or_init(|| {
+            let encoded = self.encoded.as_ref().expect("region without metadata");
+            let meta: loader::Metadata<&'static str> = bincode::options()
+                .with_varint_encoding()
+                .deserialize(encoded.metadata)
+                .expect("failed to decode embedded metadata");
+
+            #[allow(unused_mut)]
+            let mut meta = metadata(meta).expect("invalid embedded metadata");
+
+            #[cfg(feature = "compiled-patterns")]
+            compiled(&mut meta, &encoded.patterns);
+
+            meta
+        })
+    }
+}
+
+/// Use the programs compiled by the build script for every pattern of the
+/// region, so none of them is compiled at runtime.
+#[cfg(feature = "compiled-patterns")]
+fn compiled(meta: &mut super::Metadata, patterns: &[Pattern<'_>]) {
+    let programs = patterns
+        .iter()
+        .map(|p| {
+            let program: Program = bincode::options()
+                .with_varint_encoding()
+                .deserialize(p.program)
+                .expect("failed to decode embedded pattern");
+
+            (p.source, Arc::new(program))
+        })
+        .collect::<FnvHashMap<_, _>>();
+
+    for regex in patterns_mut(meta) {
+        if let Some(program) = programs.get(regex.source()) {
+            regex.set_program(program.clone());
+        }
+    }
+}
+
+/// Every pattern of the metadata.
+#[cfg(feature = "compiled-patterns")]
+fn patterns_mut(meta: &mut super::Metadata) -> Vec<&mut LazyRegex> {
+    let desc = &mut meta.descriptors;
+    let descriptors = [
+        Some(&mut desc.general),
+        desc.fixed_line.as_mut(),
+        desc.mobile.as_mut(),
+        desc.toll_free.as_mut(),
+        desc.premium_rate.as_mut(),
+        desc.shared_cost.as_mut(),
+        desc.personal_number.as_mut(),
+        desc.voip.as_mut(),
+        desc.pager.as_mut(),
+        desc.uan.as_mut(),
+        desc.emergency.as_mut(),
+        desc.voicemail.as_mut(),
+        desc.short_code.as_mut(),
+        desc.standard_rate.as_mut(),
+        desc.carrier.as_mut(),
+        desc.no_international.as_mut(),
+    ];
+
+    let mut result = descriptors
+        .into_iter()
+        .flatten()
+        .map(|d| &mut d.national_number)
+        .collect::<Vec<_>>();
+
+    result.extend(meta.international_prefix.as_mut());
+    result.extend(meta.national_prefix_for_parsing.as_mut());
+    result.extend(meta.leading_digits.as_mut());
+
+    for format in meta
+        .formats
+        .iter_mut()
+        .chain(meta.international_formats.iter_mut())
+    {
+        result.push(&mut format.pattern);
+        result.extend(format.leading_digits.iter_mut());
+    }
+
+    result
+}
+
+impl fmt::Debug for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.metadata.get() {
+            Some(meta) => fmt::Debug::fmt(meta, f),
+            None => f.write_str("<encoded>"),
+        }
+    }
 }
 
 impl Database {
@@ -51,6 +198,20 @@ impl Database {
         Database::from(loader::load(BufReader::new(File::open(path)?))?)
     }
 
+    /// Load a database from the given file, skipping unknown elements and
+    /// attributes, see `loader::load_lenient`.
+    pub fn load_lenient<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<(Self, Vec<loader::Warning>), error::LoadMetadata> {
+        let (meta, warnings) = loader::load_lenient(BufReader::new(File::open(path)?))?;
+        Ok((Database::from(meta)?, warnings))
+    }
+
+    /// Build a database from typed metadata instead of XML.
+    pub fn builder() -> DatabaseBuilder {
+        DatabaseBuilder::default()
+    }
+
     /// Parse a database from the given string.
     pub fn parse<S: AsRef<str>>(content: S) -> Result<Self, error::LoadMetadata> {
         Database::from(loader::load(Cursor::new(content.as_ref()))?)
@@ -58,175 +219,290 @@ impl Database {
 
     /// Create a database from a loaded database.
     pub fn from(meta: Vec<loader::Metadata>) -> Result<Self, error::LoadMetadata> {
-        fn tranpose<T, E>(value: Option<Result<T, E>>) -> Result<Option<T>, E> {
-            match value {
-                None => Ok(None),
+        let mut database = Database::empty();
 
-                Some(Ok(value)) => Ok(Some(value)),
+        for meta in meta {
+            let id = meta.id.clone();
+            let meta = metadata(meta).map_err(|e| e.in_territory(id))?;
 
-                Some(Err(err)) => Err(err),
-            }
+            database.insert(
+                meta.id.to_string(),
+                meta.country_code,
+                meta.main_country_for_code,
+                Region {
+                    encoded: None,
+                    metadata: OnceLock::from(meta),
+                },
+            );
         }
 
-        let cache = Arc::new(Mutex::new(RegexCache::new(100)));
-        let regex = |value: String| -> Result<CachedRegex, error::LoadMetadata> {
-            Ok(CachedRegexBuilder::new(cache.clone(), &value)
-                .ignore_whitespace(true)
-                .build()?)
-        };
+        Ok(database)
+    }
 
-        let descriptor =
-            |desc: loader::Descriptor| -> Result<super::Descriptor, error::LoadMetadata> {
-                desc.national_number.as_ref().unwrap();
-                desc.national_number.as_ref().unwrap();
-
-                Ok(super::Descriptor {
-                    national_number: desc
-                        .national_number
-                        .ok_or_else(|| {
-                            error::LoadMetadata::from(error::Metadata::MissingValue {
-                                phase: "descriptor".into(),
-                                name: "national_number".into(),
-                            })
-                        })
-                        .and_then(regex)?,
-
-                    possible_length: desc.possible_length,
-                    possible_local_length: desc.possible_local_length,
-                    example: desc.example,
-                })
-            };
+    /// Get the database used by the functions that do not take one, such as
+    /// `parse`, `is_valid` or `format`.
+    ///
+    /// This is the embedded database unless replaced with `set_current`. The
+    /// returned database is a snapshot, and is not affected by later calls to
+    /// `set_current`.
+    pub fn current() -> Arc<Database> {
+        CURRENT.load_full()
+    }
 
-        let format = |format: loader::Format| -> Result<super::Format, error::LoadMetadata> {
-            Ok(super::Format {
-                pattern: format
-                    .pattern
-                    .ok_or_else(|| {
-                        error::LoadMetadata::from(error::Metadata::MissingValue {
-                            phase: "format".into(),
-                            name: "pattern".into(),
-                        })
-                    })
-                    .and_then(regex)?,
+    /// Get a snapshot of the current database for the duration of a call,
+    /// without cloning the `Arc`.
+    pub(crate) fn snapshot() -> Guard<Arc<Database>> {
+        CURRENT.load()
+    }
 
-                format: format.format.ok_or_else(|| {
-                    error::LoadMetadata::from(error::Metadata::MissingValue {
-                        phase: "format".into(),
-                        name: "format".into(),
-                    })
-                })?,
+    /// Replace the database used by the functions that do not take one,
+    /// returning the previous one.
+    ///
+    /// Calls already in progress keep using the previous database, so every
+    /// call sees a consistent database.
+    pub fn set_current<D: Into<Arc<Database>>>(database: D) -> Arc<Database> {
+        CURRENT.swap(database.into())
+    }
 
-                leading_digits: format
-                    .leading_digits
-                    .into_iter()
-                    .map(&regex)
-                    .collect::<Result<_, _>>()?,
+    /// Go back to the embedded database for the functions that do not take
+    /// one, returning the previous one.
+    pub fn reset_current() -> Arc<Database> {
+        Database::set_current(DEFAULT.clone())
+    }
 
-                national_prefix: format.national_prefix_formatting_rule,
-                national_prefix_optional: format.national_prefix_optional_when_formatting,
+    /// Compare the metadata with the one in the given newer database.
+    pub fn diff(&self, other: &Database) -> MetadataDiff {
+        diff::diff(self, other)
+    }
 
-                domestic_carrier: format.domestic_carrier,
-            })
-        };
+    /// Classify the phone numbers with this database and the given newer one,
+    /// returning the ones whose validity or type changed.
+    pub fn reclassify<'a, I>(&self, other: &Database, numbers: I) -> Vec<Reclassified>
+    where
+        I: IntoIterator<Item = &'a PhoneNumber>,
+    {
+        diff::reclassify(self, other, numbers)
+    }
 
-        let metadata = |meta: loader::Metadata| -> Result<super::Metadata, error::LoadMetadata> {
-            Ok(super::Metadata {
-                descriptors: super::Descriptors {
-                    general: descriptor(meta.general.ok_or_else(|| {
-                        error::LoadMetadata::from(error::Metadata::MissingValue {
-                            phase: "metadata".into(),
-                            name: "generalDesc".into(),
-                        })
-                    })?)?,
-
-                    fixed_line: tranpose(meta.fixed_line.map(descriptor))?,
-                    mobile: tranpose(meta.mobile.map(descriptor))?,
-                    toll_free: tranpose(meta.toll_free.map(descriptor))?,
-                    premium_rate: tranpose(meta.premium_rate.map(descriptor))?,
-                    shared_cost: tranpose(meta.shared_cost.map(descriptor))?,
-                    personal_number: tranpose(meta.personal_number.map(descriptor))?,
-                    voip: tranpose(meta.voip.map(descriptor))?,
-                    pager: tranpose(meta.pager.map(descriptor))?,
-                    uan: tranpose(meta.uan.map(descriptor))?,
-                    emergency: tranpose(meta.emergency.map(descriptor))?,
-                    voicemail: tranpose(meta.voicemail.map(descriptor))?,
-                    short_code: tranpose(meta.short_code.map(descriptor))?,
-                    standard_rate: tranpose(meta.standard_rate.map(descriptor))?,
-                    carrier: tranpose(meta.carrier.map(descriptor))?,
-                    no_international: tranpose(meta.no_international.map(descriptor))?,
-                },
+    /// Check the metadata of every territory for inconsistencies, such as
+    /// leading digits overlapping those of another territory, or example
+    /// numbers that do not match their own pattern, are attributed to another
+    /// territory, cannot be formatted, or do not parse back to themselves once
+    /// formatted.
+    pub fn verify(&self) -> Vec<MetadataIssue> {
+        verify::verify(self)
+    }
 
-                id: meta.id.ok_or_else(|| {
-                    error::LoadMetadata::from(error::Metadata::MissingValue {
-                        phase: "metadata".into(),
-                        name: "id".into(),
-                    })
-                })?,
+    /// Iterate over the metadata of every territory, including every
+    /// non-geographical entity.
+    pub(crate) fn regions(&self) -> impl Iterator<Item = &super::Metadata> {
+        self.by_code.values().flatten().map(|r| r.get())
+    }
 
-                country_code: meta.country_code.ok_or_else(|| {
-                    error::LoadMetadata::from(error::Metadata::MissingValue {
-                        phase: "metadata".into(),
-                        name: "countryCode".into(),
-                    })
-                })?,
+    /// Convert the database back to loaded metadata, ordered by country code
+    /// with the main country for a code first.
+    pub fn export(&self) -> Vec<loader::Metadata> {
+        let mut codes = self.by_code.keys().collect::<Vec<_>>();
+        codes.sort();
 
-                international_prefix: tranpose(meta.international_prefix.map(regex))?,
-                preferred_international_prefix: meta.preferred_international_prefix,
-                national_prefix: meta.national_prefix,
-                preferred_extension_prefix: meta.preferred_extension_prefix,
-                national_prefix_for_parsing: tranpose(meta.national_prefix_for_parsing.map(regex))?,
-                national_prefix_transform_rule: meta.national_prefix_transform_rule,
-
-                formats: meta
-                    .formats
-                    .into_iter()
-                    .map(&format)
-                    .collect::<Result<_, _>>()?,
-                international_formats: meta
-                    .international_formats
-                    .into_iter()
-                    .map(&format)
-                    .collect::<Result<_, _>>()?,
-
-                main_country_for_code: meta.main_country_for_code,
-                leading_digits: tranpose(meta.leading_digits.map(regex))?,
-                mobile_number_portable: meta.mobile_number_portable,
-            })
-        };
+        codes
+            .into_iter()
+            .flat_map(|code| &self.by_code[code])
+            .map(|region| region.get().into())
+            .collect()
+    }
 
-        let mut by_id = FnvHashMap::default();
-        let mut by_code = FnvHashMap::default();
-        let mut regions = FnvHashMap::default();
+    /// Write the database in the libphonenumber XML schema, which can be read
+    /// back with `Database::load` or `Database::parse`.
+    pub fn write_xml<W: Write>(&self, writer: W) -> io::Result<()> {
+        export::xml(writer, &self.export())
+    }
 
-        for meta in meta {
-            let meta = Arc::new(metadata(meta)?);
+    /// Where the metadata of the database comes from.
+    ///
+    /// Databases created with `with_overrides` keep the version of the
+    /// database they were created from.
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
 
-            by_id.insert(meta.id.clone(), meta.clone());
+    /// Label the version of the database, e.g. with the release of locally
+    /// patched metadata.
+    pub fn with_label<S: Into<String>>(mut self, label: S) -> Self {
+        self.version.label = Some(label.into());
+        self
+    }
 
-            let by_code = by_code.entry(meta.country_code).or_insert_with(Vec::new);
+    /// Create a copy of the database with the given territories patched in.
+    ///
+    /// A territory with an unknown ID is added and must be complete, just like
+    /// when loading a database. For a known territory every value present in
+    /// the overlay replaces the current one, whole descriptors included, while
+    /// flags can only be set. Formats replace the ones with the same pattern,
+    /// the others are tried before the current formats.
+    ///
+    /// The overlay can be loaded from XML with `loader::load`, or deserialized
+    /// from any format supported by serde.
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use phonenumber::metadata::{loader, Database};
+    ///
+    /// let overlay = loader::load(Cursor::new(r#"
+    ///     <phoneNumberMetadata><territories>
+    ///         <territory id="BE">
+    ///             <mobile>
+    ///                 <possibleLengths national="9"/>
+    ///                 <nationalNumberPattern>4[4-9]\d{7}</nationalNumberPattern>
+    ///             </mobile>
+    ///         </territory>
+    ///     </territories></phoneNumberMetadata>
+    /// "#)).unwrap();
+    ///
+    /// let database = Database::current().with_overrides(overlay).unwrap();
+    /// let number = phonenumber::parse_with(&database, None, "+32 441 23 45 67").unwrap();
+    ///
+    /// assert!(!phonenumber::is_valid(&number));
+    /// assert!(phonenumber::is_valid_with(&database, &number));
+    /// ```
+    pub fn with_overrides(
+        &self,
+        overlay: Vec<loader::Metadata>,
+    ) -> Result<Self, error::LoadMetadata> {
+        let mut database = self.clone();
 
-            let regions = regions.entry(meta.country_code).or_insert_with(Vec::new);
+        for meta in overlay {
+            let id = meta.id.clone().ok_or_else(|| {
+                error::LoadMetadata::from(error::Metadata::MissingValue {
+                    phase: "metadata".into(),
+                    name: "id".into(),
+                })
+            })?;
 
-            if meta.main_country_for_code {
-                by_code.insert(0, meta.clone());
-                regions.insert(0, meta.id.clone())
-            } else {
-                by_code.push(meta.clone());
-                regions.push(meta.id.clone());
+            // Non-geographical entities all share the "001" ID.
+            let base = match (id.as_str(), meta.country_code) {
+                ("001", Some(code)) => database
+                    .by_code
+                    .get(&code)
+                    .and_then(|r| r.iter().find(|r| r.get().id == id))
+                    .cloned(),
+
+                _ => database.by_id.get(&id).cloned(),
+            };
+            let meta = match base {
+                Some(ref base) => merge(base.get(), meta),
+                None => metadata(meta),
+            }
+            .map_err(|e| e.in_territory(Some(&*id)))?;
+
+            let region = Region {
+                encoded: None,
+                metadata: OnceLock::from(meta),
+            };
+
+            match base {
+                // Keep the position of the territory within its country code.
+                Some(base)
+                    if base.get().country_code == region.get().country_code
+                        && base.get().main_country_for_code
+                            == region.get().main_country_for_code =>
+                {
+                    let region = Arc::new(region);
+                    let code = region.get().country_code;
+
+                    for entry in database.by_code.entry(code).or_default() {
+                        if Arc::ptr_eq(entry, &base) {
+                            *entry = region.clone();
+                        }
+                    }
+
+                    database.by_id.insert(id, region);
+                }
+
+                base => {
+                    if let Some(base) = base {
+                        database.remove(&id, &base);
+                    }
+
+                    let (code, main) = {
+                        let meta = region.get();
+                        (meta.country_code, meta.main_country_for_code)
+                    };
+
+                    database.insert(id, code, main, region);
+                }
             }
         }
 
-        Ok(Database {
-            cache: cache.clone(),
-            by_id,
-            by_code,
-            regions,
-        })
+        Ok(database)
     }
 
-    /// Get the regular expression cache.
-    pub fn cache(&self) -> Arc<Mutex<RegexCache>> {
-        self.cache.clone()
+    /// Create a database from the embedded encoded regions, without decoding
+    /// them.
+    fn embedded(bytes: &'static [u8]) -> Self {
+        let regions: Vec<Encoded<'static>> = bincode::options()
+            .with_varint_encoding()
+            .deserialize(bytes)
+            .expect("failed to decode embedded database");
+
+        let mut database = Database::empty();
+        database.version = Version::embedded();
+
+        for region in regions {
+            database.insert(
+                region.id.clone(),
+                region.code,
+                region.main,
+                Region {
+                    encoded: Some(region),
+                    metadata: OnceLock::new(),
+                },
+            );
+        }
+
+        database
+    }
+
+    fn empty() -> Self {
+        Database {
+            by_id: FnvHashMap::default(),
+            by_code: FnvHashMap::default(),
+            regions: FnvHashMap::default(),
+            version: Version::default(),
+        }
+    }
+
+    fn insert(&mut self, id: String, code: u16, main: bool, region: Region) {
+        let region = Arc::new(region);
+
+        self.by_id.insert(id.clone(), region.clone());
+
+        let by_code = self.by_code.entry(code).or_default();
+        let regions = self.regions.entry(code).or_default();
+
+        if main {
+            by_code.insert(0, region);
+            regions.insert(0, id)
+        } else {
+            by_code.push(region);
+            regions.push(id);
+        }
+    }
+
+    fn remove(&mut self, id: &str, region: &Arc<Region>) {
+        let code = region.get().country_code;
+
+        if self.by_id.get(id).is_some_and(|r| Arc::ptr_eq(r, region)) {
+            self.by_id.remove(id);
+        }
+
+        if let Some(by_code) = self.by_code.get_mut(&code) {
+            by_code.retain(|r| !Arc::ptr_eq(r, region));
+        }
+
+        if let Some(regions) = self.regions.get_mut(&code) {
+            regions.retain(|r| r != id);
+        }
     }
 
     /// Get a metadata entry by country ID.
@@ -235,7 +511,7 @@ impl Database {
         Q: ?Sized + Hash + Eq,
         String: Borrow<Q>,
     {
-        self.by_id.get(key).map(AsRef::as_ref)
+        self.by_id.get(key).map(|r| r.get())
     }
 
     /// Get metadata entries by country code.
@@ -246,7 +522,7 @@ impl Database {
     {
         self.by_code
             .get(key)
-            .map(|m| m.iter().map(AsRef::as_ref).collect())
+            .map(|m| m.iter().map(|r| r.get()).collect())
     }
 
     /// Get all country IDs corresponding to the given country code.
@@ -260,3 +536,822 @@ impl Database {
             .map(|m| m.iter().map(AsRef::as_ref).collect())
     }
 }
+
+fn tranpose<T, E>(value: Option<Result<T, E>>) -> Result<Option<T>, E> {
+    match value {
+        None => Ok(None),
+
+        Some(Ok(value)) => Ok(Some(value)),
+
+        Some(Err(err)) => Err(err),
+    }
+}
+
+/// Text of loaded metadata, either owned or borrowed from the embedded
+/// database.
+pub(crate) trait Text: Into<Cow<'static, str>> {
+    /// Create a pattern from the text, checking its syntax unless the build
+    /// script already did.
+    fn regex(self) -> Result<LazyRegex, regex::Error>;
+}
+
+impl Text for String {
+    fn regex(self) -> Result<LazyRegex, regex::Error> {
+        LazyRegex::new(&self)
+    }
+}
+
+impl Text for &'static str {
+    fn regex(self) -> Result<LazyRegex, regex::Error> {
+        Ok(LazyRegex::embedded(self))
+    }
+}
+
+/// Check the syntax of the pattern in the given element or attribute.
+fn regex<S: Text>(name: &str, value: S) -> Result<LazyRegex, error::LoadMetadata> {
+    value
+        .regex()
+        .map_err(|e| error::LoadMetadata::from(e).in_field(name))
+}
+
+fn optional<S: Text>(
+    name: &str,
+    desc: Option<loader::Descriptor<S>>,
+) -> Result<Option<super::Descriptor>, error::LoadMetadata> {
+    tranpose(desc.map(|desc| descriptor(name, desc)))
+}
+
+fn descriptor<S: Text>(
+    name: &str,
+    desc: loader::Descriptor<S>,
+) -> Result<super::Descriptor, error::LoadMetadata> {
+    let source = desc.national_number.ok_or_else(|| {
+        error::LoadMetadata::from(error::Metadata::MissingValue {
+            phase: "descriptor".into(),
+            name: "national_number".into(),
+        })
+        .in_field(name)
+    })?;
+
+    Ok(super::Descriptor {
+        national_number: regex("nationalNumberPattern", source).map_err(|e| e.in_field(name))?,
+
+        possible_length: desc.possible_length,
+        possible_local_length: desc.possible_local_length,
+        example: desc.example.map(Into::into),
+    })
+}
+
+fn format<S: Text>(format: loader::Format<S>) -> Result<super::Format, error::LoadMetadata> {
+    format_fields(format).map_err(|e| e.in_field("numberFormat"))
+}
+
+fn format_fields<S: Text>(format: loader::Format<S>) -> Result<super::Format, error::LoadMetadata> {
+    Ok(super::Format {
+        pattern: format
+            .pattern
+            .ok_or_else(|| {
+                error::LoadMetadata::from(error::Metadata::MissingValue {
+                    phase: "format".into(),
+                    name: "pattern".into(),
+                })
+            })
+            .and_then(|r| regex("pattern", r))?,
+
+        format: format
+            .format
+            .ok_or_else(|| {
+                error::LoadMetadata::from(error::Metadata::MissingValue {
+                    phase: "format".into(),
+                    name: "format".into(),
+                })
+            })?
+            .into(),
+
+        leading_digits: format
+            .leading_digits
+            .into_iter()
+            .map(|r| regex("leadingDigits", r))
+            .collect::<Result<_, _>>()?,
+
+        national_prefix: format.national_prefix_formatting_rule.map(Into::into),
+        national_prefix_optional: format.national_prefix_optional_when_formatting,
+
+        domestic_carrier: format.domestic_carrier.map(Into::into),
+        international: None,
+    })
+}
+
+/// Record the international format of every national format, by pattern and
+/// leading digits, leaving out the ones marked `NA`.
+fn link(meta: &mut super::Metadata) {
+    for format in &mut meta.international_formats {
+        format.international = Some(format.format.clone());
+    }
+
+    for format in &mut meta.formats {
+        format.international = meta
+            .international_formats
+            .iter()
+            .find(|i| i.pattern == format.pattern && i.leading_digits == format.leading_digits)
+            .map(|i| i.format.clone());
+    }
+}
+
+/// Record the possible and local only lengths of the general descriptor.
+///
+/// Unlike in libphonenumber, where they are merged from the other descriptors
+/// when the metadata is built, the general descriptor usually has no lengths
+/// of its own, so they are merged here.
+fn lengths(meta: &mut super::Metadata) {
+    let descriptors = &meta.descriptors;
+
+    if !descriptors.general.possible_length.is_empty() {
+        meta.general_lengths = (
+            descriptors.general.possible_length.clone(),
+            descriptors.general.possible_local_length.clone(),
+        );
+
+        return;
+    }
+
+    let mut possible = Vec::new();
+    let mut local = Vec::new();
+
+    for desc in [
+        &descriptors.fixed_line,
+        &descriptors.mobile,
+        &descriptors.toll_free,
+        &descriptors.premium_rate,
+        &descriptors.shared_cost,
+        &descriptors.personal_number,
+        &descriptors.voip,
+        &descriptors.pager,
+        &descriptors.uan,
+        &descriptors.voicemail,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        possible.extend_from_slice(&desc.possible_length);
+        local.extend_from_slice(&desc.possible_local_length);
+    }
+
+    possible.sort_unstable();
+    possible.dedup();
+    local.sort_unstable();
+    local.dedup();
+    local.retain(|length| !possible.contains(length));
+
+    meta.general_lengths = (possible, local);
+}
+
+/// Patch the values present in the loaded metadata into the given metadata.
+fn merge(
+    base: &super::Metadata,
+    overlay: loader::Metadata,
+) -> Result<super::Metadata, error::LoadMetadata> {
+    fn replace(
+        target: &mut Option<super::Descriptor>,
+        name: &str,
+        value: Option<loader::Descriptor>,
+    ) -> Result<(), error::LoadMetadata> {
+        if let Some(value) = value {
+            *target = Some(descriptor(name, value)?);
+        }
+
+        Ok(())
+    }
+
+    fn formats(
+        target: &mut Vec<super::Format>,
+        value: Vec<loader::Format>,
+    ) -> Result<(), error::LoadMetadata> {
+        let mut added = Vec::new();
+
+        for value in value {
+            let value = format(value).map_err(|e| e.in_field("availableFormats"))?;
+
+            match target
+                .iter_mut()
+                .find(|f| f.pattern.source() == value.pattern.source())
+            {
+                Some(current) => *current = value,
+                None => added.push(value),
+            }
+        }
+
+        target.splice(0..0, added);
+        Ok(())
+    }
+
+    let mut meta = base.clone();
+    let desc = &mut meta.descriptors;
+
+    if let Some(general) = overlay.general {
+        desc.general = descriptor("generalDesc", general)?;
+    }
+
+    replace(&mut desc.fixed_line, "fixedLine", overlay.fixed_line)?;
+    replace(&mut desc.mobile, "mobile", overlay.mobile)?;
+    replace(&mut desc.toll_free, "tollFree", overlay.toll_free)?;
+    replace(&mut desc.premium_rate, "premiumRate", overlay.premium_rate)?;
+    replace(&mut desc.shared_cost, "sharedCost", overlay.shared_cost)?;
+    replace(
+        &mut desc.personal_number,
+        "personalNumber",
+        overlay.personal_number,
+    )?;
+    replace(&mut desc.voip, "voip", overlay.voip)?;
+    replace(&mut desc.pager, "pager", overlay.pager)?;
+    replace(&mut desc.uan, "uan", overlay.uan)?;
+    replace(&mut desc.emergency, "emergency", overlay.emergency)?;
+    replace(&mut desc.voicemail, "voicemail", overlay.voicemail)?;
+    replace(&mut desc.short_code, "shortCode", overlay.short_code)?;
+    replace(
+        &mut desc.standard_rate,
+        "standardRate",
+        overlay.standard_rate,
+    )?;
+    replace(&mut desc.carrier, "carrierSpecific", overlay.carrier)?;
+    replace(
+        &mut desc.no_international,
+        "noInternationalDialling",
+        overlay.no_international,
+    )?;
+
+    if let Some(code) = overlay.country_code {
+        meta.country_code = code;
+    }
+
+    if let Some(value) = overlay.international_prefix {
+        meta.international_prefix = Some(regex("internationalPrefix", value)?);
+    }
+
+    if let Some(value) = overlay.national_prefix_for_parsing {
+        meta.national_prefix_for_parsing = Some(regex("nationalPrefixForParsing", value)?);
+    }
+
+    if let Some(value) = overlay.leading_digits {
+        meta.leading_digits = Some(regex("leadingDigits", value)?);
+    }
+
+    meta.preferred_international_prefix = overlay
+        .preferred_international_prefix
+        .map(Into::into)
+        .or(meta.preferred_international_prefix);
+    meta.national_prefix = overlay
+        .national_prefix
+        .map(Into::into)
+        .or(meta.national_prefix);
+    meta.preferred_extension_prefix = overlay
+        .preferred_extension_prefix
+        .map(Into::into)
+        .or(meta.preferred_extension_prefix);
+    meta.national_prefix_transform_rule = overlay
+        .national_prefix_transform_rule
+        .map(Into::into)
+        .or(meta.national_prefix_transform_rule);
+
+    formats(&mut meta.formats, overlay.formats)?;
+    formats(
+        &mut meta.international_formats,
+        overlay.international_formats,
+    )?;
+
+    meta.main_country_for_code |= overlay.main_country_for_code;
+    meta.mobile_number_portable |= overlay.mobile_number_portable;
+
+    link(&mut meta);
+    lengths(&mut meta);
+    Ok(meta)
+}
+
+/// Convert loaded metadata, checking required values and pattern syntax.
+pub(crate) fn metadata<S: Text>(
+    meta: loader::Metadata<S>,
+) -> Result<super::Metadata, error::LoadMetadata> {
+    let mut result = super::Metadata {
+        descriptors: super::Descriptors {
+            general: descriptor(
+                "generalDesc",
+                meta.general.ok_or_else(|| {
+                    error::LoadMetadata::from(error::Metadata::MissingValue {
+                        phase: "metadata".into(),
+                        name: "generalDesc".into(),
+                    })
+                })?,
+            )?,
+
+            fixed_line: optional("fixedLine", meta.fixed_line)?,
+            mobile: optional("mobile", meta.mobile)?,
+            toll_free: optional("tollFree", meta.toll_free)?,
+            premium_rate: optional("premiumRate", meta.premium_rate)?,
+            shared_cost: optional("sharedCost", meta.shared_cost)?,
+            personal_number: optional("personalNumber", meta.personal_number)?,
+            voip: optional("voip", meta.voip)?,
+            pager: optional("pager", meta.pager)?,
+            uan: optional("uan", meta.uan)?,
+            emergency: optional("emergency", meta.emergency)?,
+            voicemail: optional("voicemail", meta.voicemail)?,
+            short_code: optional("shortCode", meta.short_code)?,
+            standard_rate: optional("standardRate", meta.standard_rate)?,
+            carrier: optional("carrierSpecific", meta.carrier)?,
+            no_international: optional("noInternationalDialling", meta.no_international)?,
+        },
+
+        id: meta
+            .id
+            .ok_or_else(|| {
+                error::LoadMetadata::from(error::Metadata::MissingValue {
+                    phase: "metadata".into(),
+                    name: "id".into(),
+                })
+            })?
+            .into(),
+
+        country_code: meta.country_code.ok_or_else(|| {
+            error::LoadMetadata::from(error::Metadata::MissingValue {
+                phase: "metadata".into(),
+                name: "countryCode".into(),
+            })
+        })?,
+
+        international_prefix: tranpose(
+            meta.international_prefix
+                .map(|r| regex("internationalPrefix", r)),
+        )?,
+        preferred_international_prefix: meta.preferred_international_prefix.map(Into::into),
+        national_prefix: meta.national_prefix.map(Into::into),
+        preferred_extension_prefix: meta.preferred_extension_prefix.map(Into::into),
+        national_prefix_for_parsing: tranpose(
+            meta.national_prefix_for_parsing
+                .map(|r| regex("nationalPrefixForParsing", r)),
+        )?,
+        national_prefix_transform_rule: meta.national_prefix_transform_rule.map(Into::into),
+
+        formats: meta
+            .formats
+            .into_iter()
+            .map(format)
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.in_field("availableFormats"))?,
+        international_formats: meta
+            .international_formats
+            .into_iter()
+            .map(format)
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.in_field("availableFormats"))?,
+
+        main_country_for_code: meta.main_country_for_code,
+        leading_digits: tranpose(meta.leading_digits.map(|r| regex("leadingDigits", r)))?,
+        mobile_number_portable: meta.mobile_number_portable,
+
+        general_lengths: Default::default(),
+    };
+
+    link(&mut result);
+    lengths(&mut result);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::metadata::database::{Database, DATABASE};
+
+    #[test]
+    fn lazy() {
+        let database = Database::embedded(DATABASE);
+        let decoded = |database: &Database| {
+            database
+                .by_id
+                .values()
+                .filter(|r| r.metadata.get().is_some())
+                .count()
+        };
+
+        assert_eq!(0, decoded(&database));
+        assert_eq!(Some(vec!["BE"]), database.region(&32));
+        assert_eq!(0, decoded(&database));
+
+        assert_eq!(32, database.by_id("BE").unwrap().country_code);
+        assert_eq!(31, database.by_code(&31).unwrap()[0].country_code);
+        assert_eq!(2, decoded(&database));
+
+        assert_eq!("US", database.by_code(&1).unwrap()[0].id);
+    }
+
+    #[test]
+    fn borrowed() {
+        use std::borrow::Cow;
+
+        let database = Database::embedded(DATABASE);
+        let meta = database.by_id("BE").unwrap();
+
+        assert!(matches!(meta.id, Cow::Borrowed("BE")));
+        assert!(matches!(meta.formats[0].format, Cow::Borrowed(_)));
+        assert!(DATABASE.as_ptr_range().contains(
+            &meta
+                .descriptors()
+                .general()
+                .national_number()
+                .source()
+                .as_ptr()
+        ));
+    }
+
+    #[test]
+    fn with_overrides() {
+        use std::io::Cursor;
+
+        use crate::metadata::{loader, DEFAULT as DATABASE};
+        use crate::{error, parser, validator};
+
+        let overlay = loader::load(Cursor::new(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="BE">
+                    <mobile>
+                        <possibleLengths national="9"/>
+                        <nationalNumberPattern>4[4-9]\d{7}</nationalNumberPattern>
+                    </mobile>
+                    <availableFormats>
+                        <numberFormat pattern="(\d{3})(\d{6})">
+                            <leadingDigits>44</leadingDigits>
+                            <format>$1 $2</format>
+                        </numberFormat>
+                    </availableFormats>
+                </territory>
+                <territory id="XT" countryCode="999">
+                    <generalDesc>
+                        <nationalNumberPattern>\d{6}</nationalNumberPattern>
+                    </generalDesc>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        ))
+        .unwrap();
+
+        let database = DATABASE.with_overrides(overlay).unwrap();
+        let be = database.by_id("BE").unwrap();
+
+        assert_eq!(32, be.country_code());
+        assert_eq!("44", be.formats()[0].leading_digits()[0].source());
+        assert_eq!(
+            DATABASE.by_id("BE").unwrap().formats().len() + 1,
+            be.formats().len()
+        );
+        assert_eq!(Some(vec!["BE"]), database.region(&32));
+        assert_eq!(Some(vec!["XT"]), database.region(&999));
+        assert!(DATABASE.by_id("XT").is_none());
+
+        let number = parser::parse_with(&database, None, "+32 441 23 45 67").unwrap();
+        assert!(!validator::is_valid(&number));
+        assert!(validator::is_valid_with(&database, &number));
+
+        let number = parser::parse_with(&database, None, "+32 470 12 34 56").unwrap();
+        assert!(validator::is_valid_with(&database, &number));
+
+        let missing = loader::load(Cursor::new(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="XU" countryCode="998"></territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        ))
+        .unwrap();
+
+        let error = DATABASE.with_overrides(missing).unwrap_err();
+        assert!(matches!(
+            error,
+            error::LoadMetadata::Metadata(error::Metadata::MissingValue { .. }, _)
+        ));
+        assert_eq!(
+            Some("XU"),
+            error.location().and_then(|l| l.territory.as_deref())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use crate::error;
+
+        let error = Database::parse(
+            "<phoneNumberMetadata><territories>\n\
+             <territory id=\"BE\" countryCode=\"3x\">\n\
+             </territory>\n\
+             </territories></phoneNumberMetadata>",
+        )
+        .unwrap_err();
+
+        let location = error.location().unwrap();
+        assert_eq!(Some(2), location.line);
+        assert_eq!(Some("BE"), location.territory.as_deref());
+        assert_eq!(Some("countryCode"), location.field.as_deref());
+        assert!(matches!(error, error::LoadMetadata::Integer(..)));
+
+        let error = Database::parse(
+            r#"<phoneNumberMetadata><territories>
+                <territory id="BE" countryCode="32">
+                    <generalDesc>
+                        <nationalNumberPattern>\d{8</nationalNumberPattern>
+                    </generalDesc>
+                </territory>
+            </territories></phoneNumberMetadata>"#,
+        )
+        .unwrap_err();
+
+        let location = error.location().unwrap();
+        assert_eq!(Some(4), location.line);
+        assert_eq!(Some("BE"), location.territory.as_deref());
+        assert_eq!(
+            Some("generalDesc/nationalNumberPattern"),
+            location.field.as_deref()
+        );
+        assert!(matches!(error, error::LoadMetadata::Regex(..)));
+        assert!(error
+            .to_string()
+            .starts_with("4:48, territory BE, generalDesc/nationalNumberPattern: "));
+
+        let error = Database::parse(
+            "<phoneNumberMetadata><territories>\n\
+             <territory id=\"BE\" countryCode=\"32\" leadingDigits=\"[1-\">\n\
+             </territory>\n\
+             </territories></phoneNumberMetadata>",
+        )
+        .unwrap_err();
+
+        let location = error.location().unwrap();
+        assert_eq!(Some(2), location.line);
+        assert_eq!(Some("BE"), location.territory.as_deref());
+        assert_eq!(Some("leadingDigits"), location.field.as_deref());
+        assert!(matches!(error, error::LoadMetadata::Regex(..)));
+    }
+
+    #[test]
+    fn set_current() {
+        use std::io::Cursor;
+        use std::sync::Arc;
+
+        use crate::metadata::{loader, DEFAULT as DATABASE};
+        use crate::{parser, validator};
+
+        // Put the database back even if an assertion fails.
+        struct Restore(Arc<Database>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                Database::set_current(self.0.clone());
+            }
+        }
+
+        // Only add a territory, since other tests use the current database
+        // concurrently.
+        let overlay = loader::load(Cursor::new(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="001" countryCode="999">
+                    <generalDesc>
+                        <possibleLengths national="6"/>
+                        <nationalNumberPattern>\d{6}</nationalNumberPattern>
+                    </generalDesc>
+                    <fixedLine>
+                        <possibleLengths national="6"/>
+                        <nationalNumberPattern>\d{6}</nationalNumberPattern>
+                    </fixedLine>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        ))
+        .unwrap();
+
+        let snapshot = Database::current();
+        let _restore = Restore(snapshot.clone());
+        assert!(parser::parse(None, "+999 123456").is_err());
+
+        Database::set_current(DATABASE.with_overrides(overlay).unwrap());
+        assert!(snapshot.by_code(&999).is_none());
+
+        let number = parser::parse(None, "+999 123456").unwrap();
+        assert!(validator::is_valid(&number));
+        assert_eq!("+999123456", number.format().to_string());
+
+        let previous = Database::reset_current();
+        assert!(previous.by_code(&999).is_some());
+        assert!(Database::current().by_code(&999).is_none());
+        assert!(parser::parse(None, "+999 123456").is_err());
+        assert!(!Arc::ptr_eq(&previous, &Database::current()));
+    }
+
+    #[cfg(feature = "compiled-patterns")]
+    #[test]
+    fn compiled_patterns() {
+        use crate::country;
+        use crate::formatter::{self, Mode};
+        use crate::{parser, validator};
+
+        let database = Database::embedded(DATABASE);
+
+        for (id, country, number) in [
+            ("GB", country::GB, "07912 345678"),
+            ("DE", country::DE, "030 123456"),
+            ("US", country::US, "1 (650) 253-0000"),
+            ("AR", country::AR, "011 15-2345-6789"),
+        ] {
+            let number = parser::parse_with(&database, Some(country), number).unwrap();
+            assert!(validator::is_valid_with(&database, &number));
+            formatter::format_with(&database, &number)
+                .mode(Mode::National)
+                .to_string();
+
+            // Nothing was compiled at runtime.
+            let mut meta = database.by_id(id).unwrap().clone();
+            for pattern in super::patterns_mut(&mut meta) {
+                assert!(!pattern.is_compiled(), "{}: {}", id, pattern.source());
+            }
+        }
+    }
+
+    #[cfg(feature = "compiled-patterns")]
+    #[test]
+    fn compiled_patterns_match_regex() {
+        use crate::metadata::diff::TYPES;
+        use crate::metadata::LazyRegex;
+
+        let database = Database::embedded(DATABASE);
+
+        // Every pattern of every region, against every example number of the
+        // region with and without its national prefix.
+        for meta in database.regions() {
+            let mut values = TYPES
+                .iter()
+                .filter_map(|&kind| meta.descriptors().get(kind))
+                .filter_map(|desc| desc.example())
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>();
+
+            if let Some(prefix) = meta.national_prefix() {
+                let prefixed = values
+                    .iter()
+                    .map(|value| format!("{}{}", prefix, value))
+                    .collect::<Vec<_>>();
+
+                values.extend(prefixed);
+            }
+
+            let id = meta.id();
+            let mut meta = meta.clone();
+            for pattern in super::patterns_mut(&mut meta) {
+                let regex = LazyRegex::new(pattern.source()).unwrap();
+                let groups = |regex: &LazyRegex, value: &str| {
+                    regex.captures_at_start(value).map(|captures| {
+                        (0..captures.len())
+                            .map(|i| captures.get(i).map(ToOwned::to_owned))
+                            .collect::<Vec<_>>()
+                    })
+                };
+
+                for value in &values {
+                    assert_eq!(
+                        regex.match_at_start(value),
+                        pattern.match_at_start(value),
+                        "{}: {} on {}",
+                        id,
+                        pattern.source(),
+                        value
+                    );
+                    assert_eq!(
+                        groups(&regex, value),
+                        groups(pattern, value),
+                        "{}: {} on {}",
+                        id,
+                        pattern.source(),
+                        value
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "compiled-patterns")]
+    #[test]
+    fn compiled_patterns_match_regex_on_random_digits() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        use crate::metadata::diff::TYPES;
+        use crate::metadata::LazyRegex;
+
+        const SEED: u64 = 0x5eed;
+        const VALUES: usize = 64;
+
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let database = Database::embedded(DATABASE);
+
+        for meta in database.regions() {
+            let mut examples = TYPES
+                .iter()
+                .filter_map(|&kind| meta.descriptors().get(kind))
+                .filter_map(|desc| desc.example())
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>();
+
+            if let Some(prefix) = meta.national_prefix() {
+                let prefixed = examples
+                    .iter()
+                    .map(|value| format!("{}{}", prefix, value))
+                    .collect::<Vec<_>>();
+
+                examples.extend(prefixed);
+            }
+
+            // The replacements of the metadata, and some odd ones.
+            let mut replacements = meta
+                .formats()
+                .iter()
+                .map(|format| format.format().to_owned())
+                .collect::<Vec<_>>();
+            replacements.extend(meta.national_prefix_transform_rule().map(ToOwned::to_owned));
+            replacements.extend(["$1$2$3", "${1}0${2}x$$ $10", "$ $a ${2"].map(ToOwned::to_owned));
+
+            let id = meta.id();
+            let mut meta = meta.clone();
+            for pattern in super::patterns_mut(&mut meta) {
+                let regex = LazyRegex::new(pattern.source()).unwrap();
+                let compiled = regex.try_regex().unwrap();
+
+                for _ in 0..VALUES {
+                    let value = random_value(&mut rng, &examples);
+                    let context = || format!("{}: {} on {:?}", id, pattern.source(), value);
+
+                    let expected = regex.captures_at_start(&value);
+                    let actual = pattern.captures_at_start(&value);
+                    assert_eq!(groups(&expected), groups(&actual), "{}", context());
+
+                    if let (Some(expected), Some(actual)) = (expected, actual) {
+                        for replacement in &replacements {
+                            assert_eq!(
+                                compiled.replace(&value, replacement.as_str()),
+                                expected.replace(replacement),
+                                "{} with {:?}",
+                                context(),
+                                replacement
+                            );
+                            assert_eq!(
+                                expected.replace(replacement),
+                                actual.replace(replacement),
+                                "{} with {:?}",
+                                context(),
+                                replacement
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The text of every group.
+    #[cfg(feature = "compiled-patterns")]
+    fn groups(
+        captures: &Option<crate::metadata::lazy_regex::Captures>,
+    ) -> Option<Vec<Option<String>>> {
+        captures.as_ref().map(|captures| {
+            (0..captures.len())
+                .map(|i| captures.get(i).map(ToOwned::to_owned))
+                .collect()
+        })
+    }
+
+    /// A digit string to match patterns against: an example number, or random
+    /// digits, with a few digits changed, removed or added.
+    #[cfg(feature = "compiled-patterns")]
+    fn random_value<R: rand::Rng>(rng: &mut R, examples: &[String]) -> String {
+        let mut value = if !examples.is_empty() && rng.gen_bool(0.75) {
+            examples[rng.gen_range(0..examples.len())]
+                .clone()
+                .into_bytes()
+        } else {
+            (0..rng.gen_range(0..=17))
+                .map(|_| b'0' + rng.gen_range(0..10))
+                .collect()
+        };
+
+        for _ in 0..rng.gen_range(0..=3) {
+            let digit = b'0' + rng.gen_range(0..10);
+
+            match rng.gen_range(0..4) {
+                0 if !value.is_empty() => {
+                    let index = rng.gen_range(0..value.len());
+                    value[index] = digit;
+                }
+
+                1 if !value.is_empty() => value.truncate(rng.gen_range(0..value.len())),
+                2 => value.push(digit),
+                _ => value.insert(0, digit),
+            }
+        }
+
+        String::from_utf8(value).unwrap()
+    }
+}
@@ -12,24 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use regex_cache::CachedRegex;
+use std::borrow::Cow;
+
+use crate::metadata::LazyRegex;
 
 /// Description of a phone number to parse.
 #[derive(Clone, Debug)]
 pub struct Descriptor {
-    pub(crate) national_number: CachedRegex,
+    pub(crate) national_number: LazyRegex,
 
     pub(crate) possible_length: Vec<u16>,
     pub(crate) possible_local_length: Vec<u16>,
 
-    pub(crate) example: Option<String>,
+    pub(crate) example: Option<Cow<'static, str>>,
 }
 
 impl Descriptor {
     /// The national number is the pattern that a valid national significant
     /// number would match. This specifies information such as its total length
     /// and leading digits.
-    pub fn national_number(&self) -> &CachedRegex {
+    ///
+    /// The pattern as written in the metadata is available with
+    /// `LazyRegex::source`.
+    pub fn national_number(&self) -> &LazyRegex {
         &self.national_number
     }
 
@@ -67,7 +72,7 @@ impl Descriptor {
     /// An example national significant number for the specific type. It should
     /// not contain any formatting information.
     pub fn example(&self) -> Option<&str> {
-        self.example.as_ref().map(AsRef::as_ref)
+        self.example.as_deref()
     }
 
     /// Check if the descriptor matches the given national number.
@@ -77,9 +82,6 @@ impl Descriptor {
             return false;
         }
 
-        self.national_number
-            .find(value)
-            .map(|m| m.start() == 0)
-            .unwrap_or(false)
+        self.national_number.match_at_start(value).is_some()
     }
 }
@@ -0,0 +1,472 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::metadata::{Database, Descriptor, Format, LazyRegex, Metadata};
+use crate::phone_number::{PhoneNumber, Type};
+
+/// The descriptor types, `Unknown` being the general descriptor.
+pub(crate) const TYPES: [Type; 16] = [
+    Type::Unknown,
+    Type::FixedLine,
+    Type::Mobile,
+    Type::TollFree,
+    Type::PremiumRate,
+    Type::SharedCost,
+    Type::PersonalNumber,
+    Type::Voip,
+    Type::Pager,
+    Type::Uan,
+    Type::Emergency,
+    Type::Voicemail,
+    Type::ShortCode,
+    Type::StandardRate,
+    Type::Carrier,
+    Type::NoInternational,
+];
+
+/// Differences between two databases, see `Database::diff`.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct MetadataDiff {
+    /// Territories only in the new database.
+    pub added: Vec<Territory>,
+
+    /// Territories only in the old database.
+    pub removed: Vec<Territory>,
+
+    /// Territories in both databases with different metadata.
+    pub changed: Vec<TerritoryDiff>,
+}
+
+/// A territory, identified by its ID and country code since non-geographical
+/// entities all share the "001" ID.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Territory {
+    pub id: String,
+    pub country_code: u16,
+}
+
+/// Differences in the metadata of a territory.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TerritoryDiff {
+    pub territory: Territory,
+
+    /// Types whose national number pattern changed, including descriptors
+    /// that were added or removed, where `Unknown` is the general descriptor.
+    pub patterns: Vec<Type>,
+
+    /// Types whose possible lengths, national or local only, changed.
+    pub possible_lengths: Vec<Type>,
+
+    /// Patterns of the formats that were added, removed or changed, national
+    /// or international.
+    pub formats: Vec<String>,
+
+    /// Whether the formats in both databases are in a different order,
+    /// national or international. The first matching format is used, so
+    /// reordering them changes how numbers are formatted.
+    pub formats_reordered: bool,
+
+    /// Names of the other values that changed, e.g. `national_prefix`.
+    pub other: Vec<&'static str>,
+}
+
+/// How a phone number is classified by a database.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Classification {
+    pub valid: bool,
+    pub kind: Type,
+}
+
+/// A phone number classified differently by two databases, see
+/// `Database::reclassify`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Reclassified {
+    pub number: PhoneNumber,
+    pub before: Classification,
+    pub after: Classification,
+}
+
+impl MetadataDiff {
+    /// Whether the databases have the same metadata.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Classification {
+    /// Classify the phone number with the given database.
+    pub fn new(database: &Database, number: &PhoneNumber) -> Self {
+        Classification {
+            valid: number.is_valid_with(database),
+            kind: number.number_type(database),
+        }
+    }
+}
+
+/// Compare the territories of the two databases.
+pub(crate) fn diff(old: &Database, new: &Database) -> MetadataDiff {
+    let old = territories(old);
+    let new = territories(new);
+
+    let mut result = MetadataDiff::default();
+
+    for (territory, meta) in &old {
+        match new.iter().find(|(t, _)| t == territory) {
+            None => result.removed.push(territory.clone()),
+
+            Some((_, other)) => {
+                if let Some(diff) = territory_diff(territory, meta, other) {
+                    result.changed.push(diff);
+                }
+            }
+        }
+    }
+
+    for (territory, _) in &new {
+        if !old.iter().any(|(t, _)| t == territory) {
+            result.added.push(territory.clone());
+        }
+    }
+
+    result
+}
+
+/// Classify the phone numbers with both databases, keeping the ones that
+/// changed.
+pub(crate) fn reclassify<'a, I>(old: &Database, new: &Database, numbers: I) -> Vec<Reclassified>
+where
+    I: IntoIterator<Item = &'a PhoneNumber>,
+{
+    numbers
+        .into_iter()
+        .filter_map(|number| {
+            let before = Classification::new(old, number);
+            let after = Classification::new(new, number);
+
+            if before != after {
+                Some(Reclassified {
+                    number: number.clone(),
+                    before,
+                    after,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn territories(database: &Database) -> Vec<(Territory, &Metadata)> {
+    let mut result = database
+        .regions()
+        .map(|meta| {
+            (
+                Territory {
+                    id: meta.id.to_string(),
+                    country_code: meta.country_code,
+                },
+                meta,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    result.sort_by(|(a, _), (b, _)| (a.country_code, &a.id).cmp(&(b.country_code, &b.id)));
+    result
+}
+
+fn territory_diff(territory: &Territory, old: &Metadata, new: &Metadata) -> Option<TerritoryDiff> {
+    fn pattern(desc: Option<&Descriptor>) -> Option<&str> {
+        desc.map(|d| d.national_number.source())
+    }
+
+    fn lengths(desc: Option<&Descriptor>) -> Option<(&[u16], &[u16])> {
+        desc.map(|d| (&d.possible_length[..], &d.possible_local_length[..]))
+    }
+
+    fn source(regex: &Option<LazyRegex>) -> Option<&str> {
+        regex.as_ref().map(LazyRegex::source)
+    }
+
+    let mut result = TerritoryDiff {
+        territory: territory.clone(),
+        patterns: Vec::new(),
+        possible_lengths: Vec::new(),
+        formats: Vec::new(),
+        formats_reordered: false,
+        other: Vec::new(),
+    };
+
+    for kind in TYPES {
+        let old = old.descriptors.get(kind);
+        let new = new.descriptors.get(kind);
+
+        if pattern(old) != pattern(new) {
+            result.patterns.push(kind);
+        }
+
+        if lengths(old) != lengths(new) {
+            result.possible_lengths.push(kind);
+        }
+    }
+
+    result.formats_reordered |= formats(&mut result.formats, &old.formats, &new.formats);
+    result.formats_reordered |= formats(
+        &mut result.formats,
+        &old.international_formats,
+        &new.international_formats,
+    );
+
+    let other = [
+        (
+            "international_prefix",
+            source(&old.international_prefix) != source(&new.international_prefix),
+        ),
+        (
+            "preferred_international_prefix",
+            old.preferred_international_prefix != new.preferred_international_prefix,
+        ),
+        (
+            "national_prefix",
+            old.national_prefix != new.national_prefix,
+        ),
+        (
+            "preferred_extension_prefix",
+            old.preferred_extension_prefix != new.preferred_extension_prefix,
+        ),
+        (
+            "national_prefix_for_parsing",
+            source(&old.national_prefix_for_parsing) != source(&new.national_prefix_for_parsing),
+        ),
+        (
+            "national_prefix_transform_rule",
+            old.national_prefix_transform_rule != new.national_prefix_transform_rule,
+        ),
+        (
+            "main_country_for_code",
+            old.main_country_for_code != new.main_country_for_code,
+        ),
+        (
+            "leading_digits",
+            source(&old.leading_digits) != source(&new.leading_digits),
+        ),
+        (
+            "mobile_number_portable",
+            old.mobile_number_portable != new.mobile_number_portable,
+        ),
+    ];
+
+    result.other = other
+        .iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(name, _)| *name)
+        .collect();
+
+    if result.patterns.is_empty()
+        && result.possible_lengths.is_empty()
+        && result.formats.is_empty()
+        && !result.formats_reordered
+        && result.other.is_empty()
+    {
+        None
+    } else {
+        Some(result)
+    }
+}
+
+/// Add the patterns of the formats that changed, returning whether the
+/// formats in both lists are in a different order.
+fn formats(result: &mut Vec<String>, old: &[Format], new: &[Format]) -> bool {
+    type Key<'a> = (
+        &'a str,
+        &'a str,
+        Vec<&'a str>,
+        Option<&'a str>,
+        bool,
+        Option<&'a str>,
+    );
+
+    fn key(format: &Format) -> Key<'_> {
+        (
+            format.pattern.source(),
+            &format.format,
+            format
+                .leading_digits
+                .iter()
+                .map(LazyRegex::source)
+                .collect(),
+            format.national_prefix.as_deref(),
+            format.national_prefix_optional,
+            format.domestic_carrier.as_deref(),
+        )
+    }
+
+    let old = old.iter().map(key).collect::<Vec<_>>();
+    let new = new.iter().map(key).collect::<Vec<_>>();
+
+    for changed in old
+        .iter()
+        .filter(|k| !new.contains(k))
+        .chain(new.iter().filter(|k| !old.contains(k)))
+    {
+        if !result.iter().any(|p| p == changed.0) {
+            result.push(changed.0.into());
+        }
+    }
+
+    /// The patterns of the formats also in the other list, in order.
+    fn kept<'a>(formats: &[Key<'a>], others: &[Key<'a>]) -> Vec<&'a str> {
+        formats
+            .iter()
+            .filter(|k| others.contains(k))
+            .map(|k| k.0)
+            .collect()
+    }
+
+    kept(&old, &new) != kept(&new, &old)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::metadata::diff::{Classification, Territory};
+    use crate::metadata::{loader, Database, DEFAULT as DATABASE};
+    use crate::parser;
+    use crate::phone_number::Type;
+
+    #[test]
+    fn diff() {
+        assert!(DATABASE.diff(&DATABASE).is_empty());
+
+        let overlay = loader::load(Cursor::new(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="BE" nationalPrefix="1">
+                    <mobile>
+                        <possibleLengths national="9"/>
+                        <nationalNumberPattern>4[4-9]\d{7}</nationalNumberPattern>
+                    </mobile>
+                    <availableFormats>
+                        <numberFormat pattern="(\d{3})(\d{6})">
+                            <leadingDigits>44</leadingDigits>
+                            <format>$1 $2</format>
+                        </numberFormat>
+                    </availableFormats>
+                </territory>
+                <territory id="001" countryCode="999">
+                    <generalDesc>
+                        <nationalNumberPattern>\d{6}</nationalNumberPattern>
+                    </generalDesc>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        ))
+        .unwrap();
+
+        let patched = DATABASE.with_overrides(overlay).unwrap();
+        let diff = DATABASE.diff(&patched);
+
+        assert_eq!(
+            vec![Territory {
+                id: "001".into(),
+                country_code: 999
+            }],
+            diff.added
+        );
+        assert!(diff.removed.is_empty());
+        assert_eq!(1, diff.changed.len());
+
+        let be = &diff.changed[0];
+        assert_eq!("BE", be.territory.id);
+        assert_eq!(vec![Type::Mobile], be.patterns);
+        assert!(be.possible_lengths.is_empty());
+        assert_eq!(vec![r"(\d{3})(\d{6})".to_owned()], be.formats);
+        assert!(!be.formats_reordered);
+        assert_eq!(vec!["national_prefix"], be.other);
+
+        let reverse = patched.diff(&DATABASE);
+        assert_eq!(diff.added, reverse.removed);
+    }
+
+    #[test]
+    fn reclassify() {
+        let overlay = loader::load(Cursor::new(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="BE">
+                    <mobile>
+                        <possibleLengths national="9"/>
+                        <nationalNumberPattern>4[4-9]\d{7}</nationalNumberPattern>
+                    </mobile>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        ))
+        .unwrap();
+
+        let patched = DATABASE.with_overrides(overlay).unwrap();
+        let numbers = ["+32 441 23 45 67", "+32 470 12 34 56", "+32 2 123 45 67"]
+            .iter()
+            .map(|n| parser::parse(None, n).unwrap())
+            .collect::<Vec<_>>();
+
+        let changes = DATABASE.reclassify(&patched, &numbers);
+
+        assert_eq!(1, changes.len());
+        assert_eq!(numbers[0], changes[0].number);
+        assert_eq!(
+            Classification {
+                valid: false,
+                kind: Type::Unknown
+            },
+            changes[0].before
+        );
+        assert_eq!(
+            Classification {
+                valid: true,
+                kind: Type::Mobile
+            },
+            changes[0].after
+        );
+    }
+
+    #[test]
+    fn reordered() {
+        let territory = |first: &str, second: &str| {
+            Database::parse(format!(
+                r#"
+                <phoneNumberMetadata><territories>
+                    <territory id="BE" countryCode="32">
+                        <generalDesc>
+                            <nationalNumberPattern>\d{{8,9}}</nationalNumberPattern>
+                        </generalDesc>
+                        <availableFormats>{}{}</availableFormats>
+                    </territory>
+                </territories></phoneNumberMetadata>
+                "#,
+                first, second
+            ))
+            .unwrap()
+        };
+
+        let short = r#"<numberFormat pattern="(\d)(\d{3})(\d{4})"><format>$1 $2 $3</format></numberFormat>"#;
+        let long = r#"<numberFormat pattern="(\d{3})(\d{2})(\d{4})"><format>$1 $2 $3</format></numberFormat>"#;
+
+        let diff = territory(short, long).diff(&territory(long, short));
+
+        assert_eq!(1, diff.changed.len());
+        assert!(diff.changed[0].formats.is_empty());
+        assert!(diff.changed[0].formats_reordered);
+    }
+}
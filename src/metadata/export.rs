@@ -0,0 +1,430 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exporting metadata to XML, or to any format supported by serde.
+//!
+//! `Metadata` and `Database` serialize as `loader::Metadata` and a sequence of
+//! `loader::Metadata`, which can be deserialized back and passed to
+//! `Database::from`. In JSON a territory looks like the following, where every
+//! descriptor has the same shape as `general` and is `null` when missing, and
+//! every format has the same shape as the one in `formats`:
+//!
+//! ```json
+//! {
+//!   "general": {
+//!     "national_number": "4\\d{8}|[1-9]\\d{7}",
+//!     "possible_length": [8, 9],
+//!     "possible_local_length": [],
+//!     "example": null
+//!   },
+//!   "fixed_line": { "...": "..." },
+//!   "mobile": { "...": "..." },
+//!   "toll_free": null,
+//!   "premium_rate": null,
+//!   "shared_cost": null,
+//!   "personal_number": null,
+//!   "voip": null,
+//!   "pager": null,
+//!   "uan": null,
+//!   "emergency": null,
+//!   "voicemail": null,
+//!   "short_code": null,
+//!   "standard_rate": null,
+//!   "carrier": null,
+//!   "no_international": null,
+//!   "id": "BE",
+//!   "country_code": 32,
+//!   "international_prefix": "00",
+//!   "preferred_international_prefix": null,
+//!   "national_prefix": "0",
+//!   "preferred_extension_prefix": null,
+//!   "national_prefix_for_parsing": null,
+//!   "national_prefix_transform_rule": null,
+//!   "formats": [{
+//!     "pattern": "(\\d{3})(\\d{2})(\\d{3})",
+//!     "format": "$1 $2 $3",
+//!     "leading_digits": ["(?:80|9)0"],
+//!     "national_prefix_formatting_rule": "0$1",
+//!     "national_prefix_optional_when_formatting": false,
+//!     "domestic_carrier": null
+//!   }],
+//!   "international_formats": [],
+//!   "main_country_for_code": false,
+//!   "leading_digits": null,
+//!   "mobile_number_portable": true
+//! }
+//! ```
+//!
+//! `international_formats` lists the formats used for international numbers,
+//! which are the national ones with their `intlFormat` applied and those with
+//! an `NA` international format left out. Patterns are kept as written in the
+//! metadata.
+
+use std::io::{self, Write};
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::metadata::{loader, Database, Descriptor, Format, Metadata};
+use crate::xml::escape::escape;
+
+impl From<&Descriptor> for loader::Descriptor {
+    fn from(desc: &Descriptor) -> Self {
+        loader::Descriptor {
+            national_number: Some(desc.national_number.source().into()),
+            possible_length: desc.possible_length.clone(),
+            possible_local_length: desc.possible_local_length.clone(),
+            example: desc.example.as_deref().map(Into::into),
+        }
+    }
+}
+
+impl From<&Format> for loader::Format {
+    fn from(format: &Format) -> Self {
+        loader::Format {
+            pattern: Some(format.pattern.source().into()),
+            format: Some(format.format.to_string()),
+            leading_digits: format
+                .leading_digits
+                .iter()
+                .map(|r| r.source().into())
+                .collect(),
+            national_prefix_formatting_rule: format.national_prefix.as_deref().map(Into::into),
+            national_prefix_optional_when_formatting: format.national_prefix_optional,
+            domestic_carrier: format.domestic_carrier.as_deref().map(Into::into),
+        }
+    }
+}
+
+impl From<&Metadata> for loader::Metadata {
+    fn from(meta: &Metadata) -> Self {
+        let desc = &meta.descriptors;
+
+        loader::Metadata {
+            general: Some((&desc.general).into()),
+            fixed_line: desc.fixed_line.as_ref().map(Into::into),
+            mobile: desc.mobile.as_ref().map(Into::into),
+            toll_free: desc.toll_free.as_ref().map(Into::into),
+            premium_rate: desc.premium_rate.as_ref().map(Into::into),
+            shared_cost: desc.shared_cost.as_ref().map(Into::into),
+            personal_number: desc.personal_number.as_ref().map(Into::into),
+            voip: desc.voip.as_ref().map(Into::into),
+            pager: desc.pager.as_ref().map(Into::into),
+            uan: desc.uan.as_ref().map(Into::into),
+            emergency: desc.emergency.as_ref().map(Into::into),
+            voicemail: desc.voicemail.as_ref().map(Into::into),
+            short_code: desc.short_code.as_ref().map(Into::into),
+            standard_rate: desc.standard_rate.as_ref().map(Into::into),
+            carrier: desc.carrier.as_ref().map(Into::into),
+            no_international: desc.no_international.as_ref().map(Into::into),
+
+            id: Some(meta.id.to_string()),
+            country_code: Some(meta.country_code),
+
+            international_prefix: meta
+                .international_prefix
+                .as_ref()
+                .map(|r| r.source().into()),
+            preferred_international_prefix: meta
+                .preferred_international_prefix
+                .as_deref()
+                .map(Into::into),
+            national_prefix: meta.national_prefix.as_deref().map(Into::into),
+            preferred_extension_prefix: meta.preferred_extension_prefix.as_deref().map(Into::into),
+
+            national_prefix_for_parsing: meta
+                .national_prefix_for_parsing
+                .as_ref()
+                .map(|r| r.source().into()),
+            national_prefix_transform_rule: meta
+                .national_prefix_transform_rule
+                .as_deref()
+                .map(Into::into),
+
+            formats: meta.formats.iter().map(Into::into).collect(),
+            international_formats: meta.international_formats.iter().map(Into::into).collect(),
+
+            main_country_for_code: meta.main_country_for_code,
+            leading_digits: meta.leading_digits.as_ref().map(|r| r.source().into()),
+            mobile_number_portable: meta.mobile_number_portable,
+
+            defaults: Default::default(),
+        }
+    }
+}
+
+impl Serialize for Metadata {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        loader::Metadata::from(self).serialize(serializer)
+    }
+}
+
+impl Serialize for Database {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let metadata = self.export();
+        let mut seq = serializer.serialize_seq(Some(metadata.len()))?;
+
+        for meta in &metadata {
+            seq.serialize_element(meta)?;
+        }
+
+        seq.end()
+    }
+}
+
+/// Write the given metadata in the libphonenumber XML schema, as read by
+/// `loader::load`.
+pub fn xml<W: Write>(mut writer: W, metadata: &[loader::Metadata]) -> io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, "<phoneNumberMetadata>")?;
+    writeln!(writer, "  <territories>")?;
+
+    for meta in metadata {
+        territory(&mut writer, meta)?;
+    }
+
+    writeln!(writer, "  </territories>")?;
+    writeln!(writer, "</phoneNumberMetadata>")?;
+
+    Ok(())
+}
+
+fn territory<W: Write>(writer: &mut W, meta: &loader::Metadata) -> io::Result<()> {
+    write!(writer, "    <territory")?;
+
+    attribute(writer, "id", meta.id.as_deref())?;
+    attribute(
+        writer,
+        "countryCode",
+        meta.country_code.map(|c| c.to_string()).as_deref(),
+    )?;
+    attribute(
+        writer,
+        "mainCountryForCode",
+        Some("true").filter(|_| meta.main_country_for_code),
+    )?;
+    attribute(writer, "leadingDigits", meta.leading_digits.as_deref())?;
+    attribute(
+        writer,
+        "preferredInternationalPrefix",
+        meta.preferred_international_prefix.as_deref(),
+    )?;
+    attribute(
+        writer,
+        "internationalPrefix",
+        meta.international_prefix.as_deref(),
+    )?;
+    attribute(writer, "nationalPrefix", meta.national_prefix.as_deref())?;
+    attribute(
+        writer,
+        "nationalPrefixForParsing",
+        meta.national_prefix_for_parsing.as_deref(),
+    )?;
+    attribute(
+        writer,
+        "nationalPrefixTransformRule",
+        meta.national_prefix_transform_rule.as_deref(),
+    )?;
+    attribute(
+        writer,
+        "preferredExtnPrefix",
+        meta.preferred_extension_prefix.as_deref(),
+    )?;
+    attribute(
+        writer,
+        "mobileNumberPortableRegion",
+        Some("true").filter(|_| meta.mobile_number_portable),
+    )?;
+
+    writeln!(writer, ">")?;
+
+    if !meta.formats.is_empty() {
+        formats(writer, &meta.formats, &meta.international_formats)?;
+    }
+
+    let descriptors = [
+        ("generalDesc", &meta.general),
+        ("fixedLine", &meta.fixed_line),
+        ("mobile", &meta.mobile),
+        ("tollFree", &meta.toll_free),
+        ("premiumRate", &meta.premium_rate),
+        ("sharedCost", &meta.shared_cost),
+        ("personalNumber", &meta.personal_number),
+        ("voip", &meta.voip),
+        ("pager", &meta.pager),
+        ("uan", &meta.uan),
+        ("emergency", &meta.emergency),
+        ("voicemail", &meta.voicemail),
+        ("shortCode", &meta.short_code),
+        ("standardRate", &meta.standard_rate),
+        ("carrierSpecific", &meta.carrier),
+        ("noInternationalDialling", &meta.no_international),
+    ];
+
+    for (name, desc) in descriptors {
+        if let Some(desc) = desc {
+            descriptor(writer, name, desc)?;
+        }
+    }
+
+    writeln!(writer, "    </territory>")
+}
+
+fn formats<W: Write>(
+    writer: &mut W,
+    national: &[loader::Format],
+    international: &[loader::Format],
+) -> io::Result<()> {
+    writeln!(writer, "      <availableFormats>")?;
+
+    // International formats are derived from the national ones in order, with
+    // the ones not formatted internationally left out.
+    let mut international = international.iter().peekable();
+
+    for format in national {
+        let intl = international
+            .next_if(|i| i.pattern == format.pattern && i.leading_digits == format.leading_digits);
+
+        write!(writer, "        <numberFormat")?;
+        attribute(writer, "pattern", format.pattern.as_deref())?;
+        attribute(
+            writer,
+            "nationalPrefixFormattingRule",
+            format.national_prefix_formatting_rule.as_deref(),
+        )?;
+        attribute(
+            writer,
+            "nationalPrefixOptionalWhenFormatting",
+            Some("true").filter(|_| format.national_prefix_optional_when_formatting),
+        )?;
+        attribute(
+            writer,
+            "carrierCodeFormattingRule",
+            format.domestic_carrier.as_deref(),
+        )?;
+        writeln!(writer, ">")?;
+
+        for leading in &format.leading_digits {
+            element(writer, 10, "leadingDigits", leading)?;
+        }
+
+        if let Some(value) = format.format.as_deref() {
+            element(writer, 10, "format", value)?;
+        }
+
+        match intl.and_then(|i| i.format.as_deref()) {
+            Some(value) if Some(value) == format.format.as_deref() => (),
+            Some(value) => element(writer, 10, "intlFormat", value)?,
+            None => element(writer, 10, "intlFormat", "NA")?,
+        }
+
+        writeln!(writer, "        </numberFormat>")?;
+    }
+
+    writeln!(writer, "      </availableFormats>")
+}
+
+fn descriptor<W: Write>(writer: &mut W, name: &str, desc: &loader::Descriptor) -> io::Result<()> {
+    writeln!(writer, "      <{}>", name)?;
+
+    if !desc.possible_length.is_empty() || !desc.possible_local_length.is_empty() {
+        write!(writer, "        <possibleLengths")?;
+        attribute(
+            writer,
+            "national",
+            lengths(&desc.possible_length).as_deref(),
+        )?;
+        attribute(
+            writer,
+            "localOnly",
+            lengths(&desc.possible_local_length).as_deref(),
+        )?;
+        writeln!(writer, "/>")?;
+    }
+
+    if let Some(example) = desc.example.as_deref() {
+        element(writer, 8, "exampleNumber", example)?;
+    }
+
+    if let Some(pattern) = desc.national_number.as_deref() {
+        element(writer, 8, "nationalNumberPattern", pattern)?;
+    }
+
+    writeln!(writer, "      </{}>", name)
+}
+
+fn lengths(value: &[u16]) -> Option<String> {
+    if value.is_empty() {
+        return None;
+    }
+
+    Some(
+        value
+            .iter()
+            .map(u16::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
+fn attribute<W: Write>(writer: &mut W, name: &str, value: Option<&str>) -> io::Result<()> {
+    if let Some(value) = value {
+        write!(writer, r#" {}="{}""#, name, escape(value))?;
+    }
+
+    Ok(())
+}
+
+fn element<W: Write>(writer: &mut W, indent: usize, name: &str, value: &str) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{:indent$}<{name}>{}</{name}>",
+        "",
+        escape(value),
+        indent = indent,
+        name = name
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::metadata::{loader, Database, DEFAULT as DATABASE};
+
+    #[test]
+    fn xml() {
+        let mut xml = Vec::new();
+        DATABASE.write_xml(&mut xml).unwrap();
+
+        let database = Database::parse(String::from_utf8(xml).unwrap()).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&*DATABASE).unwrap(),
+            serde_json::to_value(&database).unwrap()
+        );
+    }
+
+    #[test]
+    fn json() {
+        let json = serde_json::to_string(&*DATABASE).unwrap();
+        let metadata: Vec<loader::Metadata> = serde_json::from_str(&json).unwrap();
+        let database = Database::from(metadata).unwrap();
+
+        assert_eq!(json, serde_json::to_string(&database).unwrap());
+
+        let be = serde_json::to_value(DATABASE.by_id("BE").unwrap()).unwrap();
+        assert_eq!("BE", be["id"]);
+        assert_eq!(32, be["country_code"]);
+        assert_eq!("4[5-9]\\d{7}", be["mobile"]["national_number"]);
+        assert_eq!(serde_json::json!([9]), be["mobile"]["possible_length"]);
+    }
+}
@@ -12,18 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use regex_cache::CachedRegex;
+use crate::metadata::LazyRegex;
+use std::borrow::Cow;
 
 /// Description of a phone number format.
 #[derive(Clone, Debug)]
 pub struct Format {
-    pub(crate) pattern: CachedRegex,
-    pub(crate) format: String,
+    pub(crate) pattern: LazyRegex,
+    pub(crate) format: Cow<'static, str>,
 
-    pub(crate) leading_digits: Vec<CachedRegex>,
-    pub(crate) national_prefix: Option<String>,
+    pub(crate) leading_digits: Vec<LazyRegex>,
+    pub(crate) national_prefix: Option<Cow<'static, str>>,
     pub(crate) national_prefix_optional: bool,
-    pub(crate) domestic_carrier: Option<String>,
+    pub(crate) domestic_carrier: Option<Cow<'static, str>>,
+    pub(crate) international: Option<Cow<'static, str>>,
 }
 
 impl Format {
@@ -33,7 +35,7 @@ impl Format {
     ///
     /// Note the presence of the parentheses, which are capturing groups what
     /// specifies the grouping of numbers.
-    pub fn pattern(&self) -> &CachedRegex {
+    pub fn pattern(&self) -> &LazyRegex {
         &self.pattern
     }
 
@@ -63,7 +65,7 @@ impl Format {
     ///
     /// In the case when only one formatting pattern exists, no
     /// leading_digits_pattern is needed.
-    pub fn leading_digits(&self) -> &[CachedRegex] {
+    pub fn leading_digits(&self) -> &[LazyRegex] {
         &self.leading_digits
     }
 
@@ -85,8 +87,17 @@ impl Format {
     /// When this field is missing, a number will be formatted without national
     /// prefix in NATIONAL format. This field does not affect how a number is
     /// formatted in other formats, such as INTERNATIONAL.
+    ///
+    /// The rule is returned as written in the metadata, with `$NP` and `$FG`
+    /// not yet replaced.
     pub fn national_prefix(&self) -> Option<&str> {
-        self.national_prefix.as_ref().map(AsRef::as_ref)
+        self.national_prefix.as_deref()
+    }
+
+    /// The `nationalPrefixFormattingRule` as written in the metadata, the same
+    /// as `national_prefix`.
+    pub fn national_prefix_formatting_rule(&self) -> Option<&str> {
+        self.national_prefix()
     }
 
     /// Whether the national prefix is optional when formatting.
@@ -99,6 +110,52 @@ impl Format {
     /// formatWithCarrierCode is called, if carrier codes are used for a certain
     /// country.
     pub fn domestic_carrier(&self) -> Option<&str> {
-        self.domestic_carrier.as_ref().map(AsRef::as_ref)
+        self.domestic_carrier.as_deref()
+    }
+
+    /// The format used when this pattern is part of an INTERNATIONAL number,
+    /// which is the `<intlFormat>` if present and `format` otherwise.
+    ///
+    /// It is `None` when the `<intlFormat>` is "NA", meaning numbers matching
+    /// this pattern are formatted as a whole in INTERNATIONAL format. The
+    /// formats in `Metadata::international_formats` always have one.
+    pub fn international_format(&self) -> Option<&str> {
+        self.international.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::metadata::DEFAULT as DATABASE;
+
+    #[test]
+    fn international_format() {
+        let ar = DATABASE.by_id("AR").unwrap();
+
+        let local = &ar.formats()[0];
+        assert_eq!(r"(\d{3})", local.pattern().source());
+        assert_eq!("$1", local.format());
+        assert_eq!(None, local.international_format());
+
+        let mobile = ar
+            .formats()
+            .iter()
+            .find(|f| f.format() != f.international_format().unwrap_or(f.format()))
+            .unwrap();
+        assert!(ar
+            .international_formats()
+            .iter()
+            .any(|f| f.pattern() == mobile.pattern()
+                && Some(f.format()) == mobile.international_format()));
+
+        let be = DATABASE.by_id("BE").unwrap();
+        assert!(be
+            .formats()
+            .iter()
+            .all(|f| f.international_format() == Some(f.format())));
+        assert_eq!(
+            Some("$NP$FG"),
+            be.formats()[0].national_prefix_formatting_rule()
+        );
     }
 }
@@ -0,0 +1,445 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loading metadata in the array form used by the JavaScript library.
+//!
+//! Every message from `phonemetadata.proto` is an array indexed by field
+//! number, so `[null, [null, null, "\\d{6}"], …]` is a territory whose general
+//! descriptor has the `\d{6}` pattern. The input is either the object mapping
+//! regions to territories, the object with that mapping in
+//! `countryToMetadata`, or a `metadata.js` file containing it.
+
+use std::io::Read;
+
+use serde_json::Value;
+
+use crate::error;
+use crate::metadata::loader;
+
+/// Load metadata in the JavaScript array form from the given reader.
+pub fn load<R: Read>(mut reader: R) -> Result<Vec<loader::Metadata>, error::LoadMetadata> {
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content)?;
+
+    let value = match content.iter().position(|c| !c.is_ascii_whitespace()) {
+        Some(start) if content[start] == b'{' => {
+            serde_json::from_slice(&content[start..]).map_err(|e| malformed(e.to_string()))?
+        }
+
+        // Skip the JavaScript around the object in `metadata.js`.
+        _ => {
+            let name = b"countryToMetadata";
+            let start = content
+                .windows(name.len())
+                .position(|w| w == name)
+                .and_then(|i| content[i..].iter().position(|&c| c == b'{').map(|j| i + j))
+                .ok_or_else(|| malformed("no countryToMetadata object"))?;
+
+            serde_json::Deserializer::from_slice(&content[start..])
+                .into_iter::<Value>()
+                .next()
+                .ok_or_else(|| malformed("no countryToMetadata object"))?
+                .map_err(|e| malformed(e.to_string()))?
+        }
+    };
+
+    let regions = match value {
+        Value::Object(mut object) => match object.remove("countryToMetadata") {
+            Some(Value::Object(regions)) => regions,
+            Some(_) => return Err(malformed("countryToMetadata is not an object")),
+            None => object,
+        },
+
+        _ => return Err(malformed("not an object")),
+    };
+
+    regions
+        .into_iter()
+        .map(|(key, value)| metadata(&value).map_err(|e| e.in_territory(Some(key))))
+        .collect()
+}
+
+fn malformed<S: Into<String>>(message: S) -> error::LoadMetadata {
+    error::LoadMetadata::malformed("json", message)
+}
+
+/// The fields of a message, by field number.
+struct Fields<'a>(&'a [Value]);
+
+impl<'a> Fields<'a> {
+    fn new(value: &'a Value) -> Result<Self, error::LoadMetadata> {
+        match value {
+            Value::Array(values) => Ok(Fields(values)),
+            _ => Err(malformed("not an array")),
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<&'a Value> {
+        self.0.get(index).filter(|v| !v.is_null())
+    }
+
+    fn string(&self, index: usize) -> Result<Option<String>, error::LoadMetadata> {
+        match self.get(index) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value.clone())),
+            Some(_) => Err(malformed("not a string")),
+        }
+    }
+
+    /// Booleans are written as `1` in the generated JavaScript.
+    fn boolean(&self, index: usize) -> Result<bool, error::LoadMetadata> {
+        match self.get(index) {
+            None => Ok(false),
+            Some(Value::Bool(value)) => Ok(*value),
+            Some(Value::Number(value)) => Ok(value.as_i64() != Some(0)),
+            Some(_) => Err(malformed("not a boolean")),
+        }
+    }
+
+    fn number(&self, index: usize) -> Result<Option<i64>, error::LoadMetadata> {
+        match self.get(index) {
+            None => Ok(None),
+            Some(value) => value
+                .as_i64()
+                .map(Some)
+                .ok_or_else(|| malformed("not an integer")),
+        }
+    }
+
+    fn array(&self, index: usize) -> Result<&'a [Value], error::LoadMetadata> {
+        match self.get(index) {
+            None => Ok(&[]),
+            Some(Value::Array(values)) => Ok(values),
+            Some(_) => Err(malformed("not an array")),
+        }
+    }
+
+    fn descriptor(
+        &self,
+        index: usize,
+        name: &str,
+    ) -> Result<Option<loader::Descriptor>, error::LoadMetadata> {
+        self.get(index)
+            .map(descriptor)
+            .transpose()
+            .map(Option::flatten)
+            .map_err(|e| e.in_field(name))
+    }
+}
+
+fn metadata(value: &Value) -> Result<loader::Metadata, error::LoadMetadata> {
+    let meta = Fields::new(value)?;
+
+    let formats = meta
+        .array(19)
+        .and_then(|f| f.iter().map(format).collect::<Result<Vec<_>, _>>())
+        .map_err(|e| e.in_field("availableFormats"))?;
+
+    // The international formats are only there when they differ from the
+    // national ones.
+    let international_formats = match meta.array(20)? {
+        [] => formats.clone(),
+        values => values
+            .iter()
+            .map(format)
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.in_field("availableFormats"))?,
+    };
+
+    Ok(loader::Metadata {
+        general: meta.descriptor(1, "generalDesc")?,
+        fixed_line: meta.descriptor(2, "fixedLine")?,
+        mobile: meta.descriptor(3, "mobile")?,
+        toll_free: meta.descriptor(4, "tollFree")?,
+        premium_rate: meta.descriptor(5, "premiumRate")?,
+        shared_cost: meta.descriptor(6, "sharedCost")?,
+        personal_number: meta.descriptor(7, "personalNumber")?,
+        voip: meta.descriptor(8, "voip")?,
+        pager: meta.descriptor(21, "pager")?,
+        uan: meta.descriptor(25, "uan")?,
+        emergency: meta.descriptor(27, "emergency")?,
+        voicemail: meta.descriptor(28, "voicemail")?,
+        short_code: meta.descriptor(29, "shortCode")?,
+        standard_rate: meta.descriptor(30, "standardRate")?,
+        carrier: meta.descriptor(31, "carrierSpecific")?,
+        no_international: meta.descriptor(24, "noInternationalDialling")?,
+
+        id: meta.string(9).map_err(|e| e.in_field("id"))?,
+        country_code: meta
+            .number(10)
+            .and_then(|c| {
+                c.map(|c| u16::try_from(c).map_err(|e| malformed(e.to_string())))
+                    .transpose()
+            })
+            .map_err(|e| e.in_field("countryCode"))?,
+
+        international_prefix: meta
+            .string(11)
+            .map_err(|e| e.in_field("internationalPrefix"))?,
+        preferred_international_prefix: meta
+            .string(17)
+            .map_err(|e| e.in_field("preferredInternationalPrefix"))?,
+        national_prefix: meta.string(12).map_err(|e| e.in_field("nationalPrefix"))?,
+        preferred_extension_prefix: meta
+            .string(13)
+            .map_err(|e| e.in_field("preferredExtnPrefix"))?,
+
+        national_prefix_for_parsing: meta
+            .string(15)
+            .map_err(|e| e.in_field("nationalPrefixForParsing"))?,
+        national_prefix_transform_rule: meta
+            .string(16)
+            .map_err(|e| e.in_field("nationalPrefixTransformRule"))?,
+
+        formats,
+        international_formats,
+
+        main_country_for_code: meta
+            .boolean(22)
+            .map_err(|e| e.in_field("mainCountryForCode"))?,
+        leading_digits: meta.string(23).map_err(|e| e.in_field("leadingDigits"))?,
+        mobile_number_portable: meta
+            .boolean(32)
+            .map_err(|e| e.in_field("mobileNumberPortableRegion"))?,
+
+        defaults: Default::default(),
+    })
+}
+
+/// Descriptors without a pattern stand for types with no numbers, which are
+/// left out of the XML.
+fn descriptor(value: &Value) -> Result<Option<loader::Descriptor>, error::LoadMetadata> {
+    fn lengths(values: &[Value]) -> Result<Vec<u16>, error::LoadMetadata> {
+        values
+            .iter()
+            .filter(|v| v.as_i64() != Some(-1))
+            .map(|v| {
+                v.as_u64()
+                    .and_then(|v| u16::try_from(v).ok())
+                    .ok_or_else(|| malformed("not a length"))
+            })
+            .collect()
+    }
+
+    let desc = Fields::new(value)?;
+
+    let national_number = match desc
+        .string(2)
+        .map_err(|e| e.in_field("nationalNumberPattern"))?
+    {
+        Some(pattern) => pattern,
+        None => return Ok(None),
+    };
+
+    Ok(Some(loader::Descriptor {
+        national_number: Some(national_number),
+        possible_length: lengths(desc.array(9)?)
+            .map_err(|e| e.in_field("possibleLengths/national"))?,
+        possible_local_length: lengths(desc.array(10)?)
+            .map_err(|e| e.in_field("possibleLengths/localOnly"))?,
+        example: desc.string(6).map_err(|e| e.in_field("exampleNumber"))?,
+    }))
+}
+
+fn format(value: &Value) -> Result<loader::Format, error::LoadMetadata> {
+    let format = Fields::new(value).map_err(|e| e.in_field("numberFormat"))?;
+
+    let result = || {
+        Ok(loader::Format {
+            pattern: format.string(1).map_err(|e| e.in_field("pattern"))?,
+            format: format.string(2).map_err(|e| e.in_field("format"))?,
+            leading_digits: format
+                .array(3)?
+                .iter()
+                .map(|v| {
+                    v.as_str()
+                        .map(Into::into)
+                        .ok_or_else(|| malformed("not a string"))
+                })
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.in_field("leadingDigits"))?,
+            national_prefix_formatting_rule: format
+                .string(4)
+                .map_err(|e| e.in_field("nationalPrefixFormattingRule"))?,
+            national_prefix_optional_when_formatting: format
+                .boolean(6)
+                .map_err(|e| e.in_field("nationalPrefixOptionalWhenFormatting"))?,
+            domestic_carrier: format
+                .string(5)
+                .map_err(|e| e.in_field("carrierCodeFormattingRule"))?,
+        })
+    };
+
+    result().map_err(|e: error::LoadMetadata| e.in_field("numberFormat"))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use crate::error;
+    use crate::metadata::{json, loader, Database, DEFAULT as DATABASE};
+
+    /// Encode the metadata in the array form, leaving trailing fields out like
+    /// the generated JavaScript does.
+    fn encode(meta: &[loader::Metadata]) -> Value {
+        fn message(mut fields: Vec<Value>) -> Value {
+            while fields.last().is_some_and(Value::is_null) {
+                fields.pop();
+            }
+
+            Value::Array(fields)
+        }
+
+        fn descriptor(desc: &Option<loader::Descriptor>) -> Value {
+            match desc {
+                None => Value::Null,
+                Some(desc) => {
+                    let mut fields = vec![Value::Null; 11];
+                    fields[2] = json!(desc.national_number);
+                    fields[6] = json!(desc.example);
+                    fields[9] = json!(desc.possible_length);
+                    fields[10] = json!(desc.possible_local_length);
+                    message(fields)
+                }
+            }
+        }
+
+        fn format(format: &loader::Format) -> Value {
+            let mut fields = vec![Value::Null; 7];
+            fields[1] = json!(format.pattern);
+            fields[2] = json!(format.format);
+            fields[3] = json!(format.leading_digits);
+            fields[4] = json!(format.national_prefix_formatting_rule);
+            fields[5] = json!(format.domestic_carrier);
+            if format.national_prefix_optional_when_formatting {
+                fields[6] = json!(1);
+            }
+            message(fields)
+        }
+
+        let mut regions = serde_json::Map::new();
+
+        for m in meta {
+            let mut fields = vec![Value::Null; 33];
+
+            for (index, desc) in [
+                (1, &m.general),
+                (2, &m.fixed_line),
+                (3, &m.mobile),
+                (4, &m.toll_free),
+                (5, &m.premium_rate),
+                (6, &m.shared_cost),
+                (7, &m.personal_number),
+                (8, &m.voip),
+                (21, &m.pager),
+                (25, &m.uan),
+                (27, &m.emergency),
+                (28, &m.voicemail),
+                (29, &m.short_code),
+                (30, &m.standard_rate),
+                (31, &m.carrier),
+                (24, &m.no_international),
+            ] {
+                fields[index] = descriptor(desc);
+            }
+
+            fields[9] = json!(m.id);
+            fields[10] = json!(m.country_code);
+            fields[11] = json!(m.international_prefix);
+            fields[12] = json!(m.national_prefix);
+            fields[13] = json!(m.preferred_extension_prefix);
+            fields[15] = json!(m.national_prefix_for_parsing);
+            fields[16] = json!(m.national_prefix_transform_rule);
+            fields[17] = json!(m.preferred_international_prefix);
+            fields[19] = Value::Array(m.formats.iter().map(format).collect());
+            if m.formats != m.international_formats {
+                fields[20] = Value::Array(m.international_formats.iter().map(format).collect());
+            }
+            if m.main_country_for_code {
+                fields[22] = json!(1);
+            }
+            fields[23] = json!(m.leading_digits);
+            if m.mobile_number_portable {
+                fields[32] = json!(1);
+            }
+
+            // Non-geographical entities are keyed by country code.
+            let key = match m.id.as_deref() {
+                Some("001") | None => m.country_code.unwrap().to_string(),
+                Some(id) => id.into(),
+            };
+
+            regions.insert(key, message(fields));
+        }
+
+        json!({ "countryToMetadata": regions })
+    }
+
+    #[test]
+    fn round_trip() {
+        let encoded = serde_json::to_vec(&encode(&DATABASE.export())).unwrap();
+        let database = Database::from(json::load(&encoded[..]).unwrap()).unwrap();
+
+        assert!(DATABASE.diff(&database).is_empty());
+    }
+
+    #[test]
+    fn javascript() {
+        let source = r#"
+            goog.provide('i18n.phonenumbers.metadata');
+            i18n.phonenumbers.metadata.countryCodeToRegionCodeMap = {
+            32:["BE"]
+            };
+            i18n.phonenumbers.metadata.countryToMetadata = {
+            "BE":[null,[null,null,"4\\d{8}|[1-9]\\d{7}",null,null,null,null,null,null,[8,9]]
+            ,[null,null,"80[2-8]\\d{5}|(?:1[0-69]|[23][2-8]|4[23]|5\\d|6[013-57-9]|71|8[1-79]|9[2-4])\\d{6}",null,null,null,"12345678",null,null,[8]]
+            ,[null,null,"4[5-9]\\d{7}",null,null,null,"470123456",null,null,[9]]
+            ,null,null,null,null,null,"BE",32,"00","0",null,null,null,null,null,null
+            ,[[null,"(\\d{3})(\\d{2})(\\d{2})(\\d{2})","$1 $2 $3 $4",["4"],"0$1"]]
+            ,null,[null,null,null,null,null,null,null,null,null,[-1]],null,null,null
+            ,[null,null,null,null,null,null,null,null,null,[-1]],null,null,null,null
+            ,null,null,1]
+            };
+        "#;
+
+        let meta = json::load(source.as_bytes()).unwrap();
+        assert_eq!(1, meta.len());
+        assert_eq!(Some("BE"), meta[0].id.as_deref());
+        assert_eq!(Some(32), meta[0].country_code);
+        assert!(meta[0].pager.is_none());
+        assert!(meta[0].mobile_number_portable);
+        assert_eq!(vec![9], meta[0].mobile.as_ref().unwrap().possible_length);
+        assert_eq!(meta[0].formats, meta[0].international_formats);
+
+        let database = Database::from(meta).unwrap();
+        let number = crate::parser::parse_with(&database, None, "+32 470 12 34 56").unwrap();
+        assert!(crate::validator::is_valid_with(&database, &number));
+    }
+
+    #[test]
+    fn malformed() {
+        let error = json::load(&br#"{"BE": [null, [null, null, 42]]}"#[..]).unwrap_err();
+
+        assert!(matches!(error, error::LoadMetadata::Malformed { .. }));
+        assert_eq!(
+            Some("BE"),
+            error.location().and_then(|l| l.territory.as_deref())
+        );
+        assert_eq!(
+            Some("generalDesc/nationalNumberPattern"),
+            error.location().and_then(|l| l.field.as_deref())
+        );
+    }
+}
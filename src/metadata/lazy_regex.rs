@@ -0,0 +1,280 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, OnceLock};
+
+use regex::{Regex, RegexBuilder};
+
+#[cfg(feature = "compiled-patterns")]
+use crate::metadata::program::Program;
+
+/// A regular expression compiled on first use.
+///
+/// The syntax is checked when it is created, so compiling it later can only
+/// fail if the expression exceeds the size limits of the `regex` crate. Once
+/// compiled, matching needs no locking, and clones share the compiled
+/// expression.
+///
+/// Whitespace in the pattern is ignored, since patterns in the metadata are
+/// often spread over several lines.
+///
+/// Matching within the crate never panics: a pattern that fails to compile
+/// matches nothing, and is reported by `Database::verify`.
+///
+/// When built with `compiled-patterns`, the patterns of the embedded database
+/// come compiled by the build script, and matching them never compiles a
+/// regular expression.
+#[derive(Clone)]
+pub struct LazyRegex {
+    source: Source,
+    regex: Arc<OnceLock<Regex>>,
+
+    #[cfg(feature = "compiled-patterns")]
+    program: Option<Arc<Program>>,
+}
+
+/// The pattern, borrowed from the embedded database when possible.
+#[derive(Clone)]
+enum Source {
+    Static(&'static str),
+    Shared(Arc<str>),
+}
+
+impl LazyRegex {
+    /// Create a new lazily compiled regular expression, checking its syntax.
+    pub fn new(source: &str) -> Result<Self, regex::Error> {
+        regex_syntax::ParserBuilder::new()
+            .ignore_whitespace(true)
+            .build()
+            .parse(source)
+            .map_err(|e| regex::Error::Syntax(e.to_string()))?;
+
+        Ok(LazyRegex {
+            source: Source::Shared(source.into()),
+            regex: Arc::new(OnceLock::new()),
+
+            #[cfg(feature = "compiled-patterns")]
+            program: None,
+        })
+    }
+
+    /// Create a new lazily compiled regular expression from the embedded
+    /// database, whose syntax was checked by the build script.
+    pub(crate) fn embedded(source: &'static str) -> Self {
+        LazyRegex {
+            source: Source::Static(source),
+            regex: Arc::new(OnceLock::new()),
+
+            #[cfg(feature = "compiled-patterns")]
+            program: None,
+        }
+    }
+
+    /// Match with the given program compiled by the build script instead.
+    #[cfg(feature = "compiled-patterns")]
+    pub(crate) fn set_program(&mut self, program: Arc<Program>) {
+        self.program = Some(program);
+    }
+
+    /// The pattern as written in the metadata.
+    pub fn source(&self) -> &str {
+        match &self.source {
+            Source::Static(source) => source,
+            Source::Shared(source) => source,
+        }
+    }
+
+    /// Get the regular expression, compiling it on first use.
+    pub fn try_regex(&self) -> Result<&Regex, regex::Error> {
+        if let Some(regex) = self.regex.get() {
+            return Ok(regex);
+        }
+
+        let regex = RegexBuilder::new(self.source())
+            .ignore_whitespace(true)
+            .build()?;
+
+        Ok(self.regex.get_or_init(|| regex))
+    }
+
+    /// Whether the regular expression was compiled at runtime.
+    #[cfg(all(test, feature = "compiled-patterns"))]
+    pub(crate) fn is_compiled(&self) -> bool {
+        self.regex.get().is_some()
+    }
+
+    /// The end of the match at the start of the string, if any.
+    pub(crate) fn match_at_start(&self, haystack: &str) -> Option<usize> {
+        #[cfg(feature = "compiled-patterns")]
+        if let Some(program) = self.program.as_ref() {
+            return program.captures(haystack.as_bytes()).and_then(|s| s[1]);
+        }
+
+        self.try_regex()
+            .ok()?
+            .find(haystack)
+            .filter(|m| m.start() == 0)
+            .map(|m| m.end())
+    }
+
+    /// The capture groups of the match at the start of the string, if any.
+    pub(crate) fn captures_at_start<'h>(&self, haystack: &'h str) -> Option<Captures<'h>> {
+        #[cfg(feature = "compiled-patterns")]
+        if let Some(program) = self.program.as_ref() {
+            return program
+                .captures(haystack.as_bytes())
+                .map(|slots| Captures { haystack, slots });
+        }
+
+        self.try_regex()
+            .ok()?
+            .captures(haystack)
+            .filter(|c| c.get(0).map(|m| m.start()) == Some(0))
+            .map(|c| Captures {
+                haystack,
+                slots: c
+                    .iter()
+                    .flat_map(|m| [m.map(|m| m.start()), m.map(|m| m.end())])
+                    .collect(),
+            })
+    }
+}
+
+/// The capture groups of a match at the start of a string.
+pub(crate) struct Captures<'h> {
+    haystack: &'h str,
+    slots: Vec<Option<usize>>,
+}
+
+impl<'h> Captures<'h> {
+    /// The number of groups, including the whole match.
+    pub fn len(&self) -> usize {
+        self.slots.len() / 2
+    }
+
+    /// The text of the group, the whole match being group 0.
+    pub fn get(&self, index: usize) -> Option<&'h str> {
+        match (self.slots.get(index * 2), self.slots.get(index * 2 + 1)) {
+            (Some(Some(start)), Some(Some(end))) => Some(&self.haystack[*start..*end]),
+            _ => None,
+        }
+    }
+
+    /// The end of the whole match.
+    pub fn end(&self) -> usize {
+        self.slots[1].unwrap_or_default()
+    }
+
+    /// Replace the match with the replacement, where `$1` or `${1}` stand for
+    /// the groups as in `Regex::replace`.
+    pub fn replace(&self, replacement: &str) -> String {
+        let mut result = String::new();
+        let mut rest = replacement;
+
+        while let Some(index) = rest.find('$') {
+            result.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+
+            if let Some(after) = rest.strip_prefix('$') {
+                result.push('$');
+                rest = after;
+                continue;
+            }
+
+            let (name, after) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], &braced[end + 1..]),
+                    None => ("", rest),
+                },
+
+                None => {
+                    let end = rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+
+                    (&rest[..end], &rest[end..])
+                }
+            };
+
+            if name.is_empty() {
+                result.push('$');
+                continue;
+            }
+
+            // There are no named groups in the metadata.
+            if let Some(group) = name.parse().ok().and_then(|i| self.get(i)) {
+                result.push_str(group);
+            }
+
+            rest = after;
+        }
+
+        result.push_str(rest);
+        result.push_str(&self.haystack[self.end()..]);
+        result
+    }
+}
+
+/// Regular expressions are compared and hashed by their source.
+impl PartialEq for LazyRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.source() == other.source()
+    }
+}
+
+impl Eq for LazyRegex {}
+
+impl Hash for LazyRegex {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source().hash(state)
+    }
+}
+
+impl fmt::Display for LazyRegex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.source())
+    }
+}
+
+impl fmt::Debug for LazyRegex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.source(), f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::metadata::LazyRegex;
+
+    #[test]
+    fn lazy() {
+        let regex = LazyRegex::new("[2-9] \\d{2}").unwrap();
+        assert!(regex.try_regex().unwrap().is_match("212"));
+        assert_eq!(Some(3), regex.match_at_start("555"));
+
+        assert!(LazyRegex::new("[2-9").is_err());
+    }
+
+    #[test]
+    fn too_big() {
+        let regex = LazyRegex::new("(?:\\w{1000}){1000}").unwrap();
+        assert!(regex.try_regex().is_err());
+
+        // Matching within the crate does not panic.
+        assert_eq!(None, regex.match_at_start("a"));
+        assert!(regex.captures_at_start("a").is_none());
+    }
+}
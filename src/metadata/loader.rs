@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
 use std::io::BufRead;
 use std::str;
 
@@ -21,6 +22,50 @@ use crate::xml::Reader;
 
 use crate::error;
 
+/// An unknown element or attribute skipped by `load_lenient`.
+#[derive(Clone, Debug)]
+pub struct Warning {
+    /// Where the element or attribute is.
+    pub location: error::Location,
+
+    /// The error it would have been when loading strictly.
+    pub error: error::Metadata,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.error)
+    }
+}
+
+/// State of the loader while reading the XML.
+struct State<'a> {
+    content: &'a [u8],
+    lenient: bool,
+    territory: Option<String>,
+    warnings: Vec<Warning>,
+}
+
+impl State<'_> {
+    /// Fail on an unknown element or attribute, or record it as a warning when
+    /// lenient.
+    fn unhandled<R>(
+        &mut self,
+        reader: &Reader<R>,
+        error: error::Metadata,
+    ) -> Result<(), error::LoadMetadata> {
+        if !self.lenient {
+            return Err(error.into());
+        }
+
+        let mut location = error::Location::at(reader.buffer_position(), self.content);
+        location.territory = self.territory.clone();
+
+        self.warnings.push(Warning { location, error });
+        Ok(())
+    }
+}
+
 /// Temporary defaults for `Format` and `Descriptor`.
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
 pub struct Defaults {
@@ -29,83 +74,151 @@ pub struct Defaults {
 }
 
 /// Parsed version of `metadata::Metadata`.
+///
+/// Strings are owned when loaded, and borrowed when decoding the embedded
+/// database.
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
-pub struct Metadata {
-    pub general: Option<Descriptor>,
-    pub fixed_line: Option<Descriptor>,
-    pub mobile: Option<Descriptor>,
-    pub toll_free: Option<Descriptor>,
-    pub premium_rate: Option<Descriptor>,
-    pub shared_cost: Option<Descriptor>,
-    pub personal_number: Option<Descriptor>,
-    pub voip: Option<Descriptor>,
-    pub pager: Option<Descriptor>,
-    pub uan: Option<Descriptor>,
-    pub emergency: Option<Descriptor>,
-    pub voicemail: Option<Descriptor>,
-    pub short_code: Option<Descriptor>,
-    pub standard_rate: Option<Descriptor>,
-    pub carrier: Option<Descriptor>,
-    pub no_international: Option<Descriptor>,
-
-    pub id: Option<String>,
+pub struct Metadata<S = String> {
+    pub general: Option<Descriptor<S>>,
+    pub fixed_line: Option<Descriptor<S>>,
+    pub mobile: Option<Descriptor<S>>,
+    pub toll_free: Option<Descriptor<S>>,
+    pub premium_rate: Option<Descriptor<S>>,
+    pub shared_cost: Option<Descriptor<S>>,
+    pub personal_number: Option<Descriptor<S>>,
+    pub voip: Option<Descriptor<S>>,
+    pub pager: Option<Descriptor<S>>,
+    pub uan: Option<Descriptor<S>>,
+    pub emergency: Option<Descriptor<S>>,
+    pub voicemail: Option<Descriptor<S>>,
+    pub short_code: Option<Descriptor<S>>,
+    pub standard_rate: Option<Descriptor<S>>,
+    pub carrier: Option<Descriptor<S>>,
+    pub no_international: Option<Descriptor<S>>,
+
+    pub id: Option<S>,
     pub country_code: Option<u16>,
 
-    pub international_prefix: Option<String>,
-    pub preferred_international_prefix: Option<String>,
-    pub national_prefix: Option<String>,
-    pub preferred_extension_prefix: Option<String>,
+    pub international_prefix: Option<S>,
+    pub preferred_international_prefix: Option<S>,
+    pub national_prefix: Option<S>,
+    pub preferred_extension_prefix: Option<S>,
 
-    pub national_prefix_for_parsing: Option<String>,
-    pub national_prefix_transform_rule: Option<String>,
+    pub national_prefix_for_parsing: Option<S>,
+    pub national_prefix_transform_rule: Option<S>,
 
-    pub formats: Vec<Format>,
-    pub international_formats: Vec<Format>,
+    pub formats: Vec<Format<S>>,
+    pub international_formats: Vec<Format<S>>,
 
     pub main_country_for_code: bool,
-    pub leading_digits: Option<String>,
+    pub leading_digits: Option<S>,
     pub mobile_number_portable: bool,
 
+    #[serde(skip)]
     pub defaults: Defaults,
 }
 
 /// Parsed version of `metadata::Format`.
-#[derive(Clone, Default, Serialize, Deserialize, Debug)]
-pub struct Format {
-    pub pattern: Option<String>,
-    pub format: Option<String>,
-    pub leading_digits: Vec<String>,
-    pub national_prefix_formatting_rule: Option<String>,
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Format<S = String> {
+    pub pattern: Option<S>,
+    pub format: Option<S>,
+    pub leading_digits: Vec<S>,
+    pub national_prefix_formatting_rule: Option<S>,
     pub national_prefix_optional_when_formatting: bool,
-    pub domestic_carrier: Option<String>,
+    pub domestic_carrier: Option<S>,
 }
 
 /// Parsed version of `metadata::Descriptor`.
-#[derive(Clone, Default, Serialize, Deserialize, Debug)]
-pub struct Descriptor {
-    pub national_number: Option<String>,
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Descriptor<S = String> {
+    pub national_number: Option<S>,
     pub possible_length: Vec<u16>,
     pub possible_local_length: Vec<u16>,
-    pub example: Option<String>,
+    pub example: Option<S>,
 }
 
 /// Load XML metadata from the given reader.
+///
+/// Errors carry the line and column in the XML where they happened, and the
+/// territory and field when known.
+///
+/// Unknown elements and attributes are errors, see `load_lenient` to skip
+/// them instead.
 pub fn load<R: BufRead>(reader: R) -> Result<Vec<Metadata>, error::LoadMetadata> {
-    metadata(&mut Reader::from_reader(reader))
+    Ok(read(reader, false)?.0)
+}
+
+/// Load XML metadata from the given reader, skipping unknown elements and
+/// attributes, e.g. those added in a newer libphonenumber release, and
+/// returning them as warnings.
+#[allow(unused)] // This is unused in the build script
+pub fn load_lenient<R: BufRead>(
+    reader: R,
+) -> Result<(Vec<Metadata>, Vec<Warning>), error::LoadMetadata> {
+    read(reader, true)
 }
 
-fn metadata<R: BufRead>(reader: &mut Reader<R>) -> Result<Vec<Metadata>, error::LoadMetadata> {
+fn read<R: BufRead>(
+    mut reader: R,
+    lenient: bool,
+) -> Result<(Vec<Metadata>, Vec<Warning>), error::LoadMetadata> {
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content)?;
+
+    let mut state = State {
+        content: &content,
+        lenient,
+        territory: None,
+        warnings: Vec::new(),
+    };
+
+    let mut reader = Reader::from_reader(&content[..]);
+    let result = metadata(&mut reader, &mut state)
+        .map_err(|err| err.at_offset(reader.buffer_position(), &content))?;
+
+    Ok((result, state.warnings))
+}
+
+/// Keep only the metadata for the given region IDs, e.g. `["DE", "FR"]`.
+///
+/// Non-geographical entities all share the "001" ID, so they are only kept if
+/// "001" is one of the given regions.
+pub fn retain<I, S>(metadata: Vec<Metadata>, regions: I) -> Vec<Metadata>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let regions = regions
+        .into_iter()
+        .map(|r| r.as_ref().trim().to_uppercase())
+        .collect::<Vec<_>>();
+
+    metadata
+        .into_iter()
+        .filter(|m| {
+            m.id.as_ref()
+                .map(|id| regions.contains(id))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+fn metadata<R: BufRead>(
+    reader: &mut Reader<R>,
+    state: &mut State<'_>,
+) -> Result<Vec<Metadata>, error::LoadMetadata> {
     let mut buffer = Vec::new();
     let mut result = Vec::new();
 
     loop {
         match reader.read_event_into(&mut buffer)? {
-            Event::Text(_) | Event::Comment(_) | Event::DocType(_) => (),
+            Event::Text(_) | Event::Comment(_) | Event::DocType(_) | Event::Decl(_) => (),
 
             Event::Start(ref e) => match e.name().into_inner() {
                 b"phoneNumberMetadata" => continue,
 
-                b"territories" => result.extend(territories(reader)?),
+                b"territories" => result.extend(territories(reader, state)?),
 
                 name => ignore(reader, name)?,
             },
@@ -132,7 +245,10 @@ fn metadata<R: BufRead>(reader: &mut Reader<R>) -> Result<Vec<Metadata>, error::
     }
 }
 
-fn territories<R: BufRead>(reader: &mut Reader<R>) -> Result<Vec<Metadata>, error::LoadMetadata> {
+fn territories<R: BufRead>(
+    reader: &mut Reader<R>,
+    state: &mut State<'_>,
+) -> Result<Vec<Metadata>, error::LoadMetadata> {
     let mut buffer = Vec::new();
     let mut result = Vec::new();
 
@@ -141,7 +257,17 @@ fn territories<R: BufRead>(reader: &mut Reader<R>) -> Result<Vec<Metadata>, erro
             Event::Text(_) | Event::Comment(_) => (),
 
             Event::Start(ref e) => match e.name().into_inner() {
-                b"territory" => result.push(territory(reader, e)?),
+                b"territory" => {
+                    let id = e
+                        .try_get_attribute("id")
+                        .ok()
+                        .flatten()
+                        .map(|a| String::from_utf8_lossy(&a.value).into_owned());
+
+                    state.territory = id.clone();
+                    result.push(territory(reader, state, e).map_err(|err| err.in_territory(id))?);
+                    state.territory = None;
+                }
 
                 name => ignore(reader, name)?,
             },
@@ -170,6 +296,7 @@ fn territories<R: BufRead>(reader: &mut Reader<R>) -> Result<Vec<Metadata>, erro
 
 fn territory<R: BufRead>(
     reader: &mut Reader<R>,
+    state: &mut State<'_>,
     e: &events::BytesStart<'_>,
 ) -> Result<Metadata, error::LoadMetadata> {
     let mut buffer = Vec::new();
@@ -181,9 +308,14 @@ fn territory<R: BufRead>(
         match (str::from_utf8(key.into_inner())?, str::from_utf8(&value)?) {
             ("id", value) => meta.id = Some(value.into()),
 
-            ("countryCode", value) => meta.country_code = Some(value.parse()?),
+            ("countryCode", value) => {
+                meta.country_code = Some(value.parse().map_err(field("countryCode"))?)
+            }
 
-            ("internationalPrefix", value) => meta.international_prefix = Some(value.into()),
+            ("internationalPrefix", value) => {
+                meta.international_prefix =
+                    Some(pattern(value.into()).map_err(field("internationalPrefix"))?)
+            }
 
             ("preferredInternationalPrefix", value) => {
                 meta.preferred_international_prefix = Some(value.into())
@@ -194,18 +326,26 @@ fn territory<R: BufRead>(
             ("preferredExtnPrefix", value) => meta.preferred_extension_prefix = Some(value.into()),
 
             ("nationalPrefixForParsing", value) => {
-                meta.national_prefix_for_parsing = Some(value.into())
+                meta.national_prefix_for_parsing =
+                    Some(pattern(value.into()).map_err(field("nationalPrefixForParsing"))?)
             }
 
             ("nationalPrefixTransformRule", value) => {
                 meta.national_prefix_transform_rule = Some(value.into())
             }
 
-            ("mainCountryForCode", value) => meta.main_country_for_code = value.parse()?,
+            ("mainCountryForCode", value) => {
+                meta.main_country_for_code = value.parse().map_err(field("mainCountryForCode"))?
+            }
 
-            ("leadingDigits", value) => meta.leading_digits = Some(value.into()),
+            ("leadingDigits", value) => {
+                meta.leading_digits = Some(pattern(value.into()).map_err(field("leadingDigits"))?)
+            }
 
-            ("mobileNumberPortableRegion", value) => meta.mobile_number_portable = value.parse()?,
+            ("mobileNumberPortableRegion", value) => {
+                meta.mobile_number_portable =
+                    value.parse().map_err(field("mobileNumberPortableRegion"))?
+            }
 
             ("nationalPrefixFormattingRule", value) => {
                 meta.defaults.format.national_prefix_formatting_rule = Some(value.into())
@@ -214,21 +354,23 @@ fn territory<R: BufRead>(
             ("nationalPrefixOptionalWhenFormatting", value) => {
                 meta.defaults
                     .format
-                    .national_prefix_optional_when_formatting = value.parse()?
+                    .national_prefix_optional_when_formatting = value
+                    .parse()
+                    .map_err(field("nationalPrefixOptionalWhenFormatting"))?
             }
 
             ("carrierCodeFormattingRule", value) => {
                 meta.defaults.format.domestic_carrier = Some(value.into())
             }
 
-            (name, value) => {
-                return Err(error::Metadata::UnhandledAttribute {
-                    phase: "format".into(),
+            (name, value) => state.unhandled(
+                reader,
+                error::Metadata::UnhandledAttribute {
+                    phase: "territory".into(),
                     name: name.into(),
                     value: value.into(),
-                }
-                .into())
-            }
+                },
+            )?,
         }
     }
 
@@ -239,52 +381,111 @@ fn territory<R: BufRead>(
             Event::Start(ref e) => match e.name().into_inner() {
                 name @ b"references" | name @ b"areaCodeOptional" => ignore(reader, name)?,
 
-                name @ b"generalDesc" => meta.general = Some(descriptor(reader, &meta, name)?),
+                name @ b"generalDesc" => {
+                    meta.general =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
-                name @ b"fixedLine" => meta.fixed_line = Some(descriptor(reader, &meta, name)?),
+                name @ b"fixedLine" => {
+                    meta.fixed_line =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
-                name @ b"mobile" => meta.mobile = Some(descriptor(reader, &meta, name)?),
+                name @ b"mobile" => {
+                    meta.mobile = Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
-                name @ b"tollFree" => meta.toll_free = Some(descriptor(reader, &meta, name)?),
+                name @ b"tollFree" => {
+                    meta.toll_free =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
-                name @ b"premiumRate" => meta.premium_rate = Some(descriptor(reader, &meta, name)?),
+                name @ b"premiumRate" => {
+                    meta.premium_rate =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
-                name @ b"sharedCost" => meta.shared_cost = Some(descriptor(reader, &meta, name)?),
+                name @ b"sharedCost" => {
+                    meta.shared_cost =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
                 name @ b"personalNumber" => {
-                    meta.personal_number = Some(descriptor(reader, &meta, name)?)
+                    meta.personal_number =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
+
+                name @ b"voip" => {
+                    meta.voip = Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
                 }
 
-                name @ b"voip" => meta.voip = Some(descriptor(reader, &meta, name)?),
+                name @ b"pager" => {
+                    meta.pager = Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
-                name @ b"pager" => meta.pager = Some(descriptor(reader, &meta, name)?),
+                name @ b"uan" => {
+                    meta.uan = Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
-                name @ b"uan" => meta.uan = Some(descriptor(reader, &meta, name)?),
+                name @ b"emergency" => {
+                    meta.emergency =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
-                name @ b"emergency" => meta.emergency = Some(descriptor(reader, &meta, name)?),
+                name @ b"voicemail" => {
+                    meta.voicemail =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
-                name @ b"voicemail" => meta.voicemail = Some(descriptor(reader, &meta, name)?),
+                name @ b"shortCode" => {
+                    meta.short_code =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
+
+                name @ b"standardRate" => {
+                    meta.standard_rate =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
+
+                name @ b"carrierSpecific" => {
+                    meta.carrier =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
+                }
 
                 name @ b"noInternationalDialling" => {
-                    meta.no_international = Some(descriptor(reader, &meta, name)?)
+                    meta.no_international =
+                        Some(descriptor(reader, state, &meta, name).map_err(field(name))?)
                 }
 
                 name @ b"availableFormats" => {
-                    let (national, international) = formats(reader, &meta, name)?;
+                    let (national, international) =
+                        formats(reader, state, &meta, name).map_err(field(name))?;
 
                     meta.formats = national;
                     meta.international_formats = international;
                 }
 
                 name => {
-                    return Err(error::Metadata::UnhandledElement {
-                        phase: "territory".into(),
-                        name: str::from_utf8(name)?.into(),
-                    }
-                    .into())
+                    state.unhandled(
+                        reader,
+                        error::Metadata::UnhandledElement {
+                            phase: "territory".into(),
+                            name: str::from_utf8(name)?.into(),
+                        },
+                    )?;
+
+                    ignore(reader, name)?
                 }
             },
 
+            Event::Empty(ref e) => state.unhandled(
+                reader,
+                error::Metadata::UnhandledElement {
+                    phase: "territory".into(),
+                    name: str::from_utf8(e.name().into_inner())?.into(),
+                },
+            )?,
+
             Event::End(ref e) if e.name().into_inner() == b"territory" => return Ok(meta),
 
             Event::End(ref e) => {
@@ -309,6 +510,7 @@ fn territory<R: BufRead>(
 
 fn descriptor<R: BufRead>(
     reader: &mut Reader<R>,
+    state: &mut State<'_>,
     meta: &Metadata,
     name: &[u8],
 ) -> Result<Descriptor, error::LoadMetadata> {
@@ -344,17 +546,21 @@ fn descriptor<R: BufRead>(
 
             Event::Start(ref e) => match e.name().into_inner() {
                 name @ b"nationalNumberPattern" => {
-                    descriptor.national_number = Some(text(reader, name)?)
+                    descriptor.national_number = Some(pattern_text(reader, state, name)?)
                 }
 
                 name @ b"exampleNumber" => descriptor.example = Some(text(reader, name)?),
 
                 name => {
-                    return Err(error::Metadata::UnhandledElement {
-                        phase: "descriptor".into(),
-                        name: str::from_utf8(name)?.into(),
-                    }
-                    .into())
+                    state.unhandled(
+                        reader,
+                        error::Metadata::UnhandledElement {
+                            phase: "descriptor".into(),
+                            name: str::from_utf8(name)?.into(),
+                        },
+                    )?;
+
+                    ignore(reader, name)?
                 }
             },
 
@@ -364,31 +570,35 @@ fn descriptor<R: BufRead>(
                         let Attribute { key, value } = attr.map_err(xml::Error::InvalidAttr)?;
 
                         match (str::from_utf8(key.into_inner())?, str::from_utf8(&value)?) {
-                            ("national", value) => descriptor.possible_length = lengths(value)?,
+                            ("national", value) => {
+                                descriptor.possible_length =
+                                    lengths(value).map_err(field("possibleLengths/national"))?
+                            }
 
                             ("localOnly", value) => {
-                                descriptor.possible_local_length = lengths(value)?
+                                descriptor.possible_local_length =
+                                    lengths(value).map_err(field("possibleLengths/localOnly"))?
                             }
 
-                            (name, value) => {
-                                return Err(error::Metadata::UnhandledAttribute {
+                            (name, value) => state.unhandled(
+                                reader,
+                                error::Metadata::UnhandledAttribute {
                                     phase: "descriptor::possibleLength".into(),
                                     name: name.into(),
                                     value: value.into(),
-                                }
-                                .into())
-                            }
+                                },
+                            )?,
                         }
                     }
                 }
 
-                name => {
-                    return Err(error::Metadata::UnhandledElement {
+                name => state.unhandled(
+                    reader,
+                    error::Metadata::UnhandledElement {
                         phase: "descriptor".into(),
                         name: str::from_utf8(name)?.into(),
-                    }
-                    .into())
-                }
+                    },
+                )?,
             },
 
             Event::End(ref e) if e.name().into_inner() == name => return Ok(descriptor),
@@ -415,6 +625,7 @@ fn descriptor<R: BufRead>(
 
 fn formats<R: BufRead>(
     reader: &mut Reader<R>,
+    state: &mut State<'_>,
     meta: &Metadata,
     name: &[u8],
 ) -> Result<(Vec<Format>, Vec<Format>), error::LoadMetadata> {
@@ -428,7 +639,7 @@ fn formats<R: BufRead>(
 
             Event::Start(ref e) => match e.name().into_inner() {
                 name @ b"numberFormat" => {
-                    let (natl, intl) = format(reader, meta, name, e)?;
+                    let (natl, intl) = format(reader, state, meta, name, e).map_err(field(name))?;
 
                     national.push(natl);
 
@@ -438,14 +649,26 @@ fn formats<R: BufRead>(
                 }
 
                 name => {
-                    return Err(error::Metadata::UnhandledElement {
-                        phase: "formats".into(),
-                        name: str::from_utf8(name)?.into(),
-                    }
-                    .into())
+                    state.unhandled(
+                        reader,
+                        error::Metadata::UnhandledElement {
+                            phase: "formats".into(),
+                            name: str::from_utf8(name)?.into(),
+                        },
+                    )?;
+
+                    ignore(reader, name)?
                 }
             },
 
+            Event::Empty(ref e) => state.unhandled(
+                reader,
+                error::Metadata::UnhandledElement {
+                    phase: "formats".into(),
+                    name: str::from_utf8(e.name().into_inner())?.into(),
+                },
+            )?,
+
             Event::End(ref e) if e.name().into_inner() == name => {
                 return Ok((national, international))
             }
@@ -472,6 +695,7 @@ fn formats<R: BufRead>(
 
 fn format<R: BufRead>(
     reader: &mut Reader<R>,
+    state: &mut State<'_>,
     meta: &Metadata,
     name: &[u8],
     e: &events::BytesStart<'_>,
@@ -485,26 +709,30 @@ fn format<R: BufRead>(
         let Attribute { key, value } = attr.map_err(xml::Error::InvalidAttr)?;
 
         match (str::from_utf8(key.into_inner())?, str::from_utf8(&value)?) {
-            ("pattern", value) => format.pattern = Some(value.into()),
+            ("pattern", value) => {
+                format.pattern = Some(pattern(value.into()).map_err(field("pattern"))?)
+            }
 
             ("nationalPrefixFormattingRule", value) => {
                 format.national_prefix_formatting_rule = Some(value.into())
             }
 
             ("nationalPrefixOptionalWhenFormatting", value) => {
-                format.national_prefix_optional_when_formatting = value.parse()?
+                format.national_prefix_optional_when_formatting = value
+                    .parse()
+                    .map_err(field("nationalPrefixOptionalWhenFormatting"))?
             }
 
             ("carrierCodeFormattingRule", value) => format.domestic_carrier = Some(value.into()),
 
-            (name, value) => {
-                return Err(error::Metadata::UnhandledAttribute {
+            (name, value) => state.unhandled(
+                reader,
+                error::Metadata::UnhandledAttribute {
                     phase: "format".into(),
                     name: name.into(),
                     value: value.into(),
-                }
-                .into())
-            }
+                },
+            )?,
         }
     }
 
@@ -513,7 +741,9 @@ fn format<R: BufRead>(
             Event::Text(_) | Event::Comment(_) => (),
 
             Event::Start(ref e) => match e.name().into_inner() {
-                name @ b"leadingDigits" => format.leading_digits.push(text(reader, name)?),
+                name @ b"leadingDigits" => format
+                    .leading_digits
+                    .push(pattern_text(reader, state, name)?),
 
                 name @ b"format" => {
                     let text = text(reader, name)?;
@@ -533,14 +763,26 @@ fn format<R: BufRead>(
                 }
 
                 name => {
-                    return Err(error::Metadata::UnhandledElement {
-                        phase: "format".into(),
-                        name: str::from_utf8(name)?.into(),
-                    }
-                    .into())
+                    state.unhandled(
+                        reader,
+                        error::Metadata::UnhandledElement {
+                            phase: "format".into(),
+                            name: str::from_utf8(name)?.into(),
+                        },
+                    )?;
+
+                    ignore(reader, name)?
                 }
             },
 
+            Event::Empty(ref e) => state.unhandled(
+                reader,
+                error::Metadata::UnhandledElement {
+                    phase: "format".into(),
+                    name: str::from_utf8(e.name().into_inner())?.into(),
+                },
+            )?,
+
             Event::End(ref e) if e.name().into_inner() == name => {
                 let international = international.map(|v| {
                     let mut format = format.clone();
@@ -634,3 +876,175 @@ fn text<R: BufRead>(reader: &mut Reader<R>, name: &[u8]) -> Result<String, error
         }
     }
 }
+
+/// Read the text of a pattern element, pointing any error at its start.
+fn pattern_text<R: BufRead>(
+    reader: &mut Reader<R>,
+    state: &State<'_>,
+    name: &[u8],
+) -> Result<String, error::LoadMetadata> {
+    let start = reader.buffer_position();
+
+    pattern(text(reader, name)?).map_err(|e| e.at_offset(start, state.content).in_field(name))
+}
+
+/// Check the syntax of a pattern, while its place in the XML is known.
+fn pattern(value: String) -> Result<String, error::LoadMetadata> {
+    regex_syntax::ParserBuilder::new()
+        .ignore_whitespace(true)
+        .build()
+        .parse(&value)
+        .map_err(|e| regex::Error::Syntax(e.to_string()))?;
+
+    Ok(value)
+}
+
+/// Record the element or attribute an error happened in.
+fn field<S, E>(name: S) -> impl FnOnce(E) -> error::LoadMetadata
+where
+    S: AsRef<[u8]>,
+    E: Into<error::LoadMetadata>,
+{
+    move |err| err.into().in_field(name)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::error;
+    use crate::metadata::loader;
+
+    const NEWER: &str = r#"<phoneNumberMetadata><territories>
+        <territory id="BE" countryCode="32" futureAttribute="yes">
+            <generalDesc>
+                <nationalNumberPattern>\d{8,9}</nationalNumberPattern>
+                <futureElement><nested/></futureElement>
+            </generalDesc>
+            <futureDesc/>
+        </territory>
+    </territories></phoneNumberMetadata>"#;
+
+    #[test]
+    fn strict() {
+        let error = loader::load(Cursor::new(NEWER)).unwrap_err();
+
+        assert!(matches!(
+            error,
+            error::LoadMetadata::Metadata(error::Metadata::UnhandledAttribute { .. }, _)
+        ));
+        assert_eq!(
+            Some("BE"),
+            error.location().and_then(|l| l.territory.as_deref())
+        );
+    }
+
+    #[test]
+    fn lenient() {
+        let (meta, warnings) = loader::load_lenient(Cursor::new(NEWER)).unwrap();
+
+        assert_eq!(1, meta.len());
+        assert_eq!(Some(32), meta[0].country_code);
+        assert_eq!(
+            Some(r"\d{8,9}"),
+            meta[0]
+                .general
+                .as_ref()
+                .and_then(|d| d.national_number.as_deref())
+        );
+
+        assert_eq!(3, warnings.len());
+        assert!(matches!(
+            &warnings[0].error,
+            error::Metadata::UnhandledAttribute { name, .. } if name == "futureAttribute"
+        ));
+        assert!(matches!(
+            &warnings[1].error,
+            error::Metadata::UnhandledElement { name, .. } if name == "futureElement"
+        ));
+        assert!(matches!(
+            &warnings[2].error,
+            error::Metadata::UnhandledElement { name, .. } if name == "futureDesc"
+        ));
+
+        assert_eq!(Some(2), warnings[0].location.line);
+        assert_eq!(Some(5), warnings[1].location.line);
+        assert_eq!(Some("BE"), warnings[2].location.territory.as_deref());
+        assert!(warnings[1].to_string().starts_with("5:"));
+    }
+
+    #[test]
+    fn descriptors() {
+        let meta = loader::load(Cursor::new(
+            r#"<phoneNumberMetadata><territories>
+                <territory id="BE" countryCode="32">
+                    <generalDesc>
+                        <nationalNumberPattern>\d{3,9}</nationalNumberPattern>
+                    </generalDesc>
+                    <shortCode>
+                        <possibleLengths national="3"/>
+                        <nationalNumberPattern>1\d\d</nationalNumberPattern>
+                    </shortCode>
+                    <standardRate>
+                        <possibleLengths national="8"/>
+                        <nationalNumberPattern>7\d{7}</nationalNumberPattern>
+                    </standardRate>
+                    <carrierSpecific>
+                        <possibleLengths national="4" localOnly="3"/>
+                        <nationalNumberPattern>8\d{3}</nationalNumberPattern>
+                    </carrierSpecific>
+                </territory>
+            </territories></phoneNumberMetadata>"#,
+        ))
+        .unwrap();
+
+        let short = meta[0].short_code.as_ref().unwrap();
+        assert_eq!(Some(r"1\d\d"), short.national_number.as_deref());
+        assert_eq!(vec![3], short.possible_length);
+
+        let standard = meta[0].standard_rate.as_ref().unwrap();
+        assert_eq!(Some(r"7\d{7}"), standard.national_number.as_deref());
+        assert_eq!(vec![8], standard.possible_length);
+
+        let carrier = meta[0].carrier.as_ref().unwrap();
+        assert_eq!(Some(r"8\d{3}"), carrier.national_number.as_deref());
+        assert_eq!(vec![4], carrier.possible_length);
+        assert_eq!(vec![3], carrier.possible_local_length);
+    }
+
+    #[test]
+    fn empty() {
+        let error = loader::load(Cursor::new(
+            r#"<phoneNumberMetadata><territories>
+                <territory id="BE" countryCode="32">
+                    <futureDesc/>
+                </territory>
+            </territories></phoneNumberMetadata>"#,
+        ))
+        .unwrap_err();
+
+        assert!(matches!(
+            &error,
+            error::LoadMetadata::Metadata(error::Metadata::UnhandledElement { name, .. }, _)
+                if name == "futureDesc"
+        ));
+        assert_eq!(Some(3), error.location().and_then(|l| l.line));
+
+        let error = loader::load(Cursor::new(
+            r#"<phoneNumberMetadata><territories>
+                <territory id="BE" countryCode="32">
+                    <carrierSpecific>
+                        <possibleLengths national="x"/>
+                    </carrierSpecific>
+                </territory>
+            </territories></phoneNumberMetadata>"#,
+        ))
+        .unwrap_err();
+
+        assert!(matches!(error, error::LoadMetadata::Integer(..)));
+        assert_eq!(
+            Some("carrierSpecific/possibleLengths/national"),
+            error.location().and_then(|l| l.field.as_deref())
+        );
+    }
+}
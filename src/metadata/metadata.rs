@@ -12,31 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::metadata::LazyRegex;
 use crate::{
     metadata::{Descriptor, Format},
     phone_number::Type,
 };
-use regex_cache::CachedRegex;
+use std::borrow::Cow;
 
 /// Phone number metadata.
 #[derive(Clone, Debug)]
 pub struct Metadata {
     pub(crate) descriptors: Descriptors,
-    pub(crate) id: String,
+    pub(crate) id: Cow<'static, str>,
     pub(crate) country_code: u16,
 
-    pub(crate) international_prefix: Option<CachedRegex>,
-    pub(crate) preferred_international_prefix: Option<String>,
-    pub(crate) national_prefix: Option<String>,
-    pub(crate) preferred_extension_prefix: Option<String>,
-    pub(crate) national_prefix_for_parsing: Option<CachedRegex>,
-    pub(crate) national_prefix_transform_rule: Option<String>,
+    pub(crate) international_prefix: Option<LazyRegex>,
+    pub(crate) preferred_international_prefix: Option<Cow<'static, str>>,
+    pub(crate) national_prefix: Option<Cow<'static, str>>,
+    pub(crate) preferred_extension_prefix: Option<Cow<'static, str>>,
+    pub(crate) national_prefix_for_parsing: Option<LazyRegex>,
+    pub(crate) national_prefix_transform_rule: Option<Cow<'static, str>>,
 
     pub(crate) formats: Vec<Format>,
     pub(crate) international_formats: Vec<Format>,
     pub(crate) main_country_for_code: bool,
-    pub(crate) leading_digits: Option<CachedRegex>,
+    pub(crate) leading_digits: Option<LazyRegex>,
     pub(crate) mobile_number_portable: bool,
+
+    /// The possible and local only lengths of the general descriptor, merged
+    /// from the other descriptors when it has none of its own.
+    pub(crate) general_lengths: (Vec<u16>, Vec<u16>),
 }
 
 /// Descriptors for various types of phone number.
@@ -87,7 +92,7 @@ impl Metadata {
     /// by the country code for country B. Note that some countries may have more
     /// than one international prefix, and for those cases, a regular expression
     /// matching the international prefixes will be stored in this field.
-    pub fn international_prefix(&self) -> Option<&CachedRegex> {
+    pub fn international_prefix(&self) -> Option<&LazyRegex> {
         self.international_prefix.as_ref()
     }
 
@@ -96,9 +101,7 @@ impl Metadata {
     /// is not present, and multiple international prefixes are present, then "+"
     /// will be used instead.
     pub fn preferred_international_prefix(&self) -> Option<&str> {
-        self.preferred_international_prefix
-            .as_ref()
-            .map(AsRef::as_ref)
+        self.preferred_international_prefix.as_deref()
     }
 
     /// The national prefix of country A is the number that needs to be dialled
@@ -108,7 +111,7 @@ impl Metadata {
     /// dialled from overseas as +64 9 345 3456. In this case, 0 is the national
     /// prefix.
     pub fn national_prefix(&self) -> Option<&str> {
-        self.national_prefix.as_ref().map(AsRef::as_ref)
+        self.national_prefix.as_deref()
     }
 
     /// The preferred prefix when specifying an extension in this country. This
@@ -119,7 +122,7 @@ impl Metadata {
     /// 1 (365) 345 445 ext. 2345
     /// " ext. "  should be the preferred extension prefix.
     pub fn preferred_extension_prefix(&self) -> Option<&str> {
-        self.preferred_extension_prefix.as_ref().map(AsRef::as_ref)
+        self.preferred_extension_prefix.as_deref()
     }
 
     /// This field is used for cases where the national prefix of a country
@@ -132,7 +135,7 @@ impl Metadata {
     ///
     /// When it is missing from the XML file, this field inherits the value of
     /// national prefix, if that is present.
-    pub fn national_prefix_for_parsing(&self) -> Option<&CachedRegex> {
+    pub fn national_prefix_for_parsing(&self) -> Option<&LazyRegex> {
         self.national_prefix_for_parsing.as_ref()
     }
 
@@ -145,9 +148,7 @@ impl Metadata {
     /// the number into a particular representation for storing in the
     /// phonenumber proto buffer in those rare cases.
     pub fn national_prefix_transform_rule(&self) -> Option<&str> {
-        self.national_prefix_transform_rule
-            .as_ref()
-            .map(AsRef::as_ref)
+        self.national_prefix_transform_rule.as_deref()
     }
 
     /// Note that the number format here is used for formatting only, not
@@ -213,7 +214,7 @@ impl Metadata {
     /// It is used merely as a short-cut for working out which region a number
     /// comes from in the case that there is only one, so leading digit prefixes
     /// should not overlap.
-    pub fn leading_digits(&self) -> Option<&CachedRegex> {
+    pub fn leading_digits(&self) -> Option<&LazyRegex> {
         self.leading_digits.as_ref()
     }
 
@@ -12,6 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod lazy_regex;
+pub use self::lazy_regex::LazyRegex;
+
+#[cfg(feature = "compiled-patterns")]
+mod program;
+
 mod format;
 pub use self::format::Format;
 
@@ -22,8 +28,57 @@ pub use self::descriptor::Descriptor;
 mod metadata;
 pub use self::metadata::{Descriptors, Metadata};
 
+mod version;
+pub use self::version::Version;
+
+pub mod diff;
+pub use self::diff::{MetadataDiff, Reclassified};
+
+mod verify;
+pub use self::verify::MetadataIssue;
+
+mod builder;
+pub use self::builder::{DatabaseBuilder, FormatBuilder, MetadataBuilder};
+
 mod database;
-pub use self::database::{Database, DEFAULT as DATABASE};
+pub use self::database::Database;
+pub(crate) use self::database::DEFAULT;
+
+/// The database embedded at build time.
+///
+/// This is not the database `parse`, `is_valid` and the other functions
+/// without an explicit database use once another one is set with
+/// `Database::set_current`, so callers using it would ignore the replacement.
+/// Use `Database::current` instead, or `Database::reset_current` to go back to
+/// the embedded one.
+#[deprecated(
+    since = "0.4.0",
+    note = "use `Database::current`, which follows `Database::set_current`"
+)]
+pub static DATABASE: Embedded = Embedded { _private: () };
+
+/// The type of the deprecated `DATABASE`, dereferencing to the embedded
+/// database.
+#[doc(hidden)]
+pub struct Embedded {
+    _private: (),
+}
+
+impl std::ops::Deref for Embedded {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        &DEFAULT
+    }
+}
 
 /// XML loading helpers.
 pub mod loader;
+
+#[cfg(feature = "protobuf")]
+pub mod proto;
+
+#[cfg(feature = "json")]
+pub mod json;
+
+pub mod export;
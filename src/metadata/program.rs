@@ -0,0 +1,333 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::RefCell;
+
+use regex_syntax::hir::{Class, Hir, HirKind, Look};
+
+/// A pattern compiled by the build script, matched with a bounded backtracker.
+///
+/// Matches are only looked for at the start of the string, and are the same
+/// as the leftmost-first match of the `regex` crate, capture groups included.
+/// Patterns are compiled without Unicode support, since they only ever match
+/// ASCII digits.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Program {
+    insts: Vec<Inst>,
+    slots: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+enum Inst {
+    /// Match the byte and go to the next instruction.
+    Byte(u8),
+
+    /// Match a byte in one of the ranges and go to the next instruction.
+    Class(Vec<(u8, u8)>),
+
+    /// Go to both instructions, preferring the first.
+    Split(u32, u32),
+
+    /// Go to the instruction.
+    Jump(u32),
+
+    /// Record the position in the capture slot and go to the next instruction.
+    Save(u32),
+
+    /// Check for the start of the string and go to the next instruction.
+    Start,
+
+    /// Check for the end of the string and go to the next instruction.
+    End,
+
+    Match,
+}
+
+/// What is left to do when backtracking.
+enum Job {
+    Explore(usize, usize),
+    Restore(usize, Option<usize>),
+}
+
+/// Buffers reused by the searches on a thread, so searching does not allocate
+/// them every time.
+#[derive(Default)]
+struct Scratch {
+    /// The search in which each state was last explored.
+    visited: Vec<u32>,
+    search: u32,
+    stack: Vec<Job>,
+}
+
+thread_local! {
+    static SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::default());
+}
+
+/// The most states whose marks are kept for the next search on the thread, so
+/// a single long haystack does not keep its buffers allocated for good.
+const KEPT_STATES: usize = 1 << 18;
+
+impl Program {
+    /// Compile the pattern, with whitespace ignored as in the metadata.
+    #[allow(unused)] // This is unused at runtime.
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let hir = regex_syntax::ParserBuilder::new()
+            .ignore_whitespace(true)
+            .unicode(false)
+            .build()
+            .parse(pattern)
+            .map_err(|e| e.to_string())?;
+
+        let mut insts = vec![Inst::Save(0)];
+        compile(&hir, &mut insts)?;
+        insts.push(Inst::Save(1));
+        insts.push(Inst::Match);
+
+        Ok(Program {
+            insts,
+            slots: (hir.properties().explicit_captures_len() as u32 + 1) * 2,
+        })
+    }
+
+    /// Find the match at the start of the string, returning the start and end
+    /// of every capture group, the whole match first.
+    #[allow(unused)] // This is unused in the build script.
+    pub fn captures(&self, haystack: &[u8]) -> Option<Vec<Option<usize>>> {
+        SCRATCH.with(|scratch| {
+            let mut scratch = scratch.borrow_mut();
+            let result = self.search(&mut scratch, haystack);
+
+            if scratch.visited.len() > KEPT_STATES || scratch.stack.capacity() > KEPT_STATES {
+                *scratch = Scratch::default();
+            }
+
+            result
+        })
+    }
+
+    fn search(&self, scratch: &mut Scratch, haystack: &[u8]) -> Option<Vec<Option<usize>>> {
+        let positions = haystack.len() + 1;
+        let states = self.insts.len() * positions;
+
+        // States are marked with the search that explored them, so the marks
+        // of previous searches need no clearing.
+        scratch.search = scratch.search.wrapping_add(1);
+        if scratch.search == 0 {
+            scratch.visited.fill(0);
+            scratch.search = 1;
+        }
+
+        if scratch.visited.len() < states {
+            scratch.visited.resize(states, 0);
+        }
+
+        let search = scratch.search;
+        let visited = &mut scratch.visited;
+        let stack = &mut scratch.stack;
+        stack.clear();
+        stack.push(Job::Explore(0, 0));
+
+        let mut slots = vec![None; self.slots as usize];
+
+        while let Some(job) = stack.pop() {
+            let (mut pc, mut at) = match job {
+                Job::Explore(pc, at) => (pc, at),
+
+                Job::Restore(slot, value) => {
+                    slots[slot] = value;
+                    continue;
+                }
+            };
+
+            // A state that was already explored failed to match before, and
+            // will fail again.
+            while std::mem::replace(&mut visited[pc * positions + at], search) != search {
+                match &self.insts[pc] {
+                    Inst::Byte(b) if haystack.get(at) == Some(b) => {
+                        pc += 1;
+                        at += 1;
+                    }
+
+                    Inst::Byte(_) => break,
+
+                    Inst::Class(ranges) => match haystack.get(at) {
+                        Some(b) if ranges.iter().any(|&(lo, hi)| lo <= *b && *b <= hi) => {
+                            pc += 1;
+                            at += 1;
+                        }
+
+                        _ => break,
+                    },
+
+                    Inst::Split(first, second) => {
+                        stack.push(Job::Explore(*second as usize, at));
+                        pc = *first as usize;
+                    }
+
+                    Inst::Jump(target) => pc = *target as usize,
+
+                    Inst::Save(slot) => {
+                        let slot = *slot as usize;
+                        stack.push(Job::Restore(slot, slots[slot]));
+                        slots[slot] = Some(at);
+                        pc += 1;
+                    }
+
+                    Inst::Start if at == 0 => pc += 1,
+                    Inst::End if at == haystack.len() => pc += 1,
+                    Inst::Start | Inst::End => break,
+
+                    Inst::Match => return Some(slots),
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[allow(unused)] // This is unused at runtime.
+fn compile(hir: &Hir, insts: &mut Vec<Inst>) -> Result<(), String> {
+    fn split(first: usize, second: usize, greedy: bool) -> Inst {
+        if greedy {
+            Inst::Split(first as u32, second as u32)
+        } else {
+            Inst::Split(second as u32, first as u32)
+        }
+    }
+
+    match hir.kind() {
+        HirKind::Empty => (),
+
+        HirKind::Literal(literal) => insts.extend(literal.0.iter().map(|&b| Inst::Byte(b))),
+
+        HirKind::Class(Class::Bytes(class)) => insts.push(Inst::Class(
+            class
+                .ranges()
+                .iter()
+                .map(|r| (r.start(), r.end()))
+                .collect(),
+        )),
+
+        HirKind::Class(Class::Unicode(class)) => match class.to_byte_class() {
+            Some(class) => insts.push(Inst::Class(
+                class
+                    .ranges()
+                    .iter()
+                    .map(|r| (r.start(), r.end()))
+                    .collect(),
+            )),
+
+            None => return Err(format!("unsupported class {:?}", class)),
+        },
+
+        HirKind::Look(Look::Start) => insts.push(Inst::Start),
+        HirKind::Look(Look::End) => insts.push(Inst::End),
+        HirKind::Look(look) => return Err(format!("unsupported assertion {:?}", look)),
+
+        HirKind::Capture(capture) => {
+            insts.push(Inst::Save(capture.index * 2));
+            compile(&capture.sub, insts)?;
+            insts.push(Inst::Save(capture.index * 2 + 1));
+        }
+
+        HirKind::Concat(subs) => {
+            for sub in subs {
+                compile(sub, insts)?;
+            }
+        }
+
+        HirKind::Alternation(subs) => {
+            let mut jumps = Vec::new();
+
+            for (i, sub) in subs.iter().enumerate() {
+                if i + 1 == subs.len() {
+                    compile(sub, insts)?;
+                    break;
+                }
+
+                // The split and the jump are patched once their targets are
+                // known.
+                let start = insts.len();
+                insts.push(Inst::Jump(0));
+                compile(sub, insts)?;
+                jumps.push(insts.len());
+                insts.push(Inst::Jump(0));
+                insts[start] = split(start + 1, insts.len(), true);
+            }
+
+            for jump in jumps {
+                insts[jump] = Inst::Jump(insts.len() as u32);
+            }
+        }
+
+        HirKind::Repetition(repetition) => {
+            for _ in 0..repetition.min {
+                compile(&repetition.sub, insts)?;
+            }
+
+            match repetition.max {
+                None => {
+                    let start = insts.len();
+                    insts.push(Inst::Jump(0));
+                    compile(&repetition.sub, insts)?;
+                    insts.push(Inst::Jump(start as u32));
+                    insts[start] = split(start + 1, insts.len(), repetition.greedy);
+                }
+
+                Some(max) => {
+                    let mut splits = Vec::new();
+
+                    for _ in repetition.min..max {
+                        splits.push(insts.len());
+                        insts.push(Inst::Jump(0));
+                        compile(&repetition.sub, insts)?;
+                    }
+
+                    for start in splits {
+                        insts[start] = split(start + 1, insts.len(), repetition.greedy);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Program, KEPT_STATES, SCRATCH};
+
+    #[test]
+    fn long_haystack() {
+        let program = Program::new(r"(\d+)").unwrap();
+        let haystack = vec![b'1'; KEPT_STATES];
+        let end = Some(haystack.len());
+
+        assert_eq!(
+            Some(vec![Some(0), end, Some(0), end]),
+            program.captures(&haystack)
+        );
+
+        // The buffers of the long haystack are not kept.
+        SCRATCH.with(|scratch| assert!(scratch.borrow().visited.is_empty()));
+
+        assert_eq!(
+            Some(vec![Some(0), Some(3), Some(0), Some(3)]),
+            program.captures(b"123")
+        );
+        SCRATCH.with(|scratch| assert!(!scratch.borrow().visited.is_empty()));
+    }
+}
@@ -0,0 +1,365 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loading metadata compiled to protocol buffers.
+//!
+//! This is the `PhoneMetadataCollection` message from libphonenumber's
+//! `phonemetadata.proto`, as written by `BuildMetadataProtoFromXml` and
+//! embedded in the C++ library.
+//!
+//! The `PhoneNumberMetadataProto_*` resources of the Java library are not
+//! supported. They hold one collection per region, along with a separate map
+//! of country codes, written with Java serialization rather than as protocol
+//! buffers, and `load` rejects them. Load the XML they are built from
+//! instead.
+
+use std::io::Read;
+
+use prost::Message;
+
+use crate::error;
+use crate::metadata::loader;
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct PhoneMetadataCollection {
+    #[prost(message, repeated, tag = "1")]
+    pub metadata: Vec<PhoneMetadata>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct PhoneMetadata {
+    #[prost(message, optional, tag = "1")]
+    pub general_desc: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "2")]
+    pub fixed_line: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "3")]
+    pub mobile: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "4")]
+    pub toll_free: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "5")]
+    pub premium_rate: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "6")]
+    pub shared_cost: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "7")]
+    pub personal_number: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "8")]
+    pub voip: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "21")]
+    pub pager: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "25")]
+    pub uan: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "27")]
+    pub emergency: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "28")]
+    pub voicemail: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "29")]
+    pub short_code: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "30")]
+    pub standard_rate: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "31")]
+    pub carrier_specific: Option<PhoneNumberDesc>,
+    #[prost(message, optional, tag = "24")]
+    pub no_international_dialling: Option<PhoneNumberDesc>,
+
+    #[prost(string, optional, tag = "9")]
+    pub id: Option<String>,
+    #[prost(int32, optional, tag = "10")]
+    pub country_code: Option<i32>,
+    #[prost(string, optional, tag = "11")]
+    pub international_prefix: Option<String>,
+    #[prost(string, optional, tag = "17")]
+    pub preferred_international_prefix: Option<String>,
+    #[prost(string, optional, tag = "12")]
+    pub national_prefix: Option<String>,
+    #[prost(string, optional, tag = "13")]
+    pub preferred_extn_prefix: Option<String>,
+    #[prost(string, optional, tag = "15")]
+    pub national_prefix_for_parsing: Option<String>,
+    #[prost(string, optional, tag = "16")]
+    pub national_prefix_transform_rule: Option<String>,
+
+    #[prost(message, repeated, tag = "19")]
+    pub number_format: Vec<NumberFormat>,
+    #[prost(message, repeated, tag = "20")]
+    pub intl_number_format: Vec<NumberFormat>,
+
+    #[prost(bool, optional, tag = "22")]
+    pub main_country_for_code: Option<bool>,
+    #[prost(string, optional, tag = "23")]
+    pub leading_digits: Option<String>,
+    #[prost(bool, optional, tag = "32")]
+    pub mobile_number_portable_region: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct PhoneNumberDesc {
+    #[prost(string, optional, tag = "2")]
+    pub national_number_pattern: Option<String>,
+    #[prost(int32, repeated, packed = "false", tag = "9")]
+    pub possible_length: Vec<i32>,
+    #[prost(int32, repeated, packed = "false", tag = "10")]
+    pub possible_length_local_only: Vec<i32>,
+    #[prost(string, optional, tag = "6")]
+    pub example_number: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct NumberFormat {
+    #[prost(string, optional, tag = "1")]
+    pub pattern: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub format: Option<String>,
+    #[prost(string, repeated, tag = "3")]
+    pub leading_digits_pattern: Vec<String>,
+    #[prost(string, optional, tag = "4")]
+    pub national_prefix_formatting_rule: Option<String>,
+    #[prost(bool, optional, tag = "6")]
+    pub national_prefix_optional_when_formatting: Option<bool>,
+    #[prost(string, optional, tag = "5")]
+    pub domestic_carrier_code_formatting_rule: Option<String>,
+}
+
+/// Load a serialized `PhoneMetadataCollection` from the given reader.
+pub fn load<R: Read>(mut reader: R) -> Result<Vec<loader::Metadata>, error::LoadMetadata> {
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content)?;
+
+    // The magic number of Java serialization streams.
+    if content.starts_with(&[0xAC, 0xED]) {
+        return Err(error::LoadMetadata::malformed(
+            "protobuf",
+            "Java serialized metadata is not supported",
+        ));
+    }
+
+    let collection = PhoneMetadataCollection::decode(&content[..])
+        .map_err(|e| error::LoadMetadata::malformed("protobuf", e.to_string()))?;
+
+    collection.metadata.into_iter().map(metadata).collect()
+}
+
+fn metadata(meta: PhoneMetadata) -> Result<loader::Metadata, error::LoadMetadata> {
+    let id = meta.id.clone();
+    let formats = meta
+        .number_format
+        .into_iter()
+        .map(format)
+        .collect::<Vec<_>>();
+
+    // The international formats are only there when they differ from the
+    // national ones.
+    let international_formats = if meta.intl_number_format.is_empty() {
+        formats.clone()
+    } else {
+        meta.intl_number_format.into_iter().map(format).collect()
+    };
+
+    Ok(loader::Metadata {
+        general: meta.general_desc.and_then(descriptor),
+        fixed_line: meta.fixed_line.and_then(descriptor),
+        mobile: meta.mobile.and_then(descriptor),
+        toll_free: meta.toll_free.and_then(descriptor),
+        premium_rate: meta.premium_rate.and_then(descriptor),
+        shared_cost: meta.shared_cost.and_then(descriptor),
+        personal_number: meta.personal_number.and_then(descriptor),
+        voip: meta.voip.and_then(descriptor),
+        pager: meta.pager.and_then(descriptor),
+        uan: meta.uan.and_then(descriptor),
+        emergency: meta.emergency.and_then(descriptor),
+        voicemail: meta.voicemail.and_then(descriptor),
+        short_code: meta.short_code.and_then(descriptor),
+        standard_rate: meta.standard_rate.and_then(descriptor),
+        carrier: meta.carrier_specific.and_then(descriptor),
+        no_international: meta.no_international_dialling.and_then(descriptor),
+
+        id: meta.id,
+        country_code: meta
+            .country_code
+            .map(u16::try_from)
+            .transpose()
+            .map_err(|e| {
+                error::LoadMetadata::malformed("protobuf", e.to_string())
+                    .in_territory(id)
+                    .in_field("countryCode")
+            })?,
+
+        international_prefix: meta.international_prefix,
+        preferred_international_prefix: meta.preferred_international_prefix,
+        national_prefix: meta.national_prefix,
+        preferred_extension_prefix: meta.preferred_extn_prefix,
+
+        national_prefix_for_parsing: meta.national_prefix_for_parsing,
+        national_prefix_transform_rule: meta.national_prefix_transform_rule,
+
+        formats,
+        international_formats,
+
+        main_country_for_code: meta.main_country_for_code.unwrap_or(false),
+        leading_digits: meta.leading_digits,
+        mobile_number_portable: meta.mobile_number_portable_region.unwrap_or(false),
+
+        defaults: Default::default(),
+    })
+}
+
+/// Descriptors without a pattern stand for types with no numbers, which are
+/// left out of the XML.
+fn descriptor(desc: PhoneNumberDesc) -> Option<loader::Descriptor> {
+    fn lengths(lengths: Vec<i32>) -> Vec<u16> {
+        lengths
+            .into_iter()
+            .filter_map(|l| u16::try_from(l).ok())
+            .collect()
+    }
+
+    Some(loader::Descriptor {
+        national_number: Some(desc.national_number_pattern?),
+        possible_length: lengths(desc.possible_length),
+        possible_local_length: lengths(desc.possible_length_local_only),
+        example: desc.example_number,
+    })
+}
+
+fn format(format: NumberFormat) -> loader::Format {
+    loader::Format {
+        pattern: format.pattern,
+        format: format.format,
+        leading_digits: format.leading_digits_pattern,
+        national_prefix_formatting_rule: format.national_prefix_formatting_rule,
+        national_prefix_optional_when_formatting: format
+            .national_prefix_optional_when_formatting
+            .unwrap_or(false),
+        domestic_carrier: format.domestic_carrier_code_formatting_rule,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prost::Message;
+
+    use crate::metadata::proto::{self, NumberFormat, PhoneMetadataCollection};
+    use crate::metadata::{loader, Database, DEFAULT as DATABASE};
+
+    fn encode(meta: &[loader::Metadata]) -> Vec<u8> {
+        fn descriptor(desc: &Option<loader::Descriptor>) -> Option<proto::PhoneNumberDesc> {
+            desc.as_ref().map(|desc| proto::PhoneNumberDesc {
+                national_number_pattern: desc.national_number.clone(),
+                possible_length: desc.possible_length.iter().map(|&l| l.into()).collect(),
+                possible_length_local_only: desc
+                    .possible_local_length
+                    .iter()
+                    .map(|&l| l.into())
+                    .collect(),
+                example_number: desc.example.clone(),
+            })
+        }
+
+        fn format(format: &loader::Format) -> NumberFormat {
+            NumberFormat {
+                pattern: format.pattern.clone(),
+                format: format.format.clone(),
+                leading_digits_pattern: format.leading_digits.clone(),
+                national_prefix_formatting_rule: format.national_prefix_formatting_rule.clone(),
+                national_prefix_optional_when_formatting: Some(
+                    format.national_prefix_optional_when_formatting,
+                ),
+                domestic_carrier_code_formatting_rule: format.domestic_carrier.clone(),
+            }
+        }
+
+        let metadata = meta
+            .iter()
+            .map(|m| proto::PhoneMetadata {
+                general_desc: descriptor(&m.general),
+                fixed_line: descriptor(&m.fixed_line),
+                mobile: descriptor(&m.mobile),
+                toll_free: descriptor(&m.toll_free),
+                premium_rate: descriptor(&m.premium_rate),
+                shared_cost: descriptor(&m.shared_cost),
+                personal_number: descriptor(&m.personal_number),
+                voip: descriptor(&m.voip),
+                pager: descriptor(&m.pager),
+                uan: descriptor(&m.uan),
+                emergency: descriptor(&m.emergency),
+                voicemail: descriptor(&m.voicemail),
+                short_code: descriptor(&m.short_code),
+                standard_rate: descriptor(&m.standard_rate),
+                carrier_specific: descriptor(&m.carrier),
+                no_international_dialling: descriptor(&m.no_international),
+                id: m.id.clone(),
+                country_code: m.country_code.map(Into::into),
+                international_prefix: m.international_prefix.clone(),
+                preferred_international_prefix: m.preferred_international_prefix.clone(),
+                national_prefix: m.national_prefix.clone(),
+                preferred_extn_prefix: m.preferred_extension_prefix.clone(),
+                national_prefix_for_parsing: m.national_prefix_for_parsing.clone(),
+                national_prefix_transform_rule: m.national_prefix_transform_rule.clone(),
+                number_format: m.formats.iter().map(format).collect(),
+                intl_number_format: if m.formats == m.international_formats {
+                    Vec::new()
+                } else {
+                    m.international_formats.iter().map(format).collect()
+                },
+                main_country_for_code: Some(m.main_country_for_code),
+                leading_digits: m.leading_digits.clone(),
+                mobile_number_portable_region: Some(m.mobile_number_portable),
+            })
+            .collect();
+
+        PhoneMetadataCollection { metadata }.encode_to_vec()
+    }
+
+    #[test]
+    fn round_trip() {
+        let encoded = encode(&DATABASE.export());
+        let database = Database::from(proto::load(&encoded[..]).unwrap()).unwrap();
+
+        assert!(DATABASE.diff(&database).is_empty());
+    }
+
+    #[test]
+    fn no_numbers() {
+        let encoded = PhoneMetadataCollection {
+            metadata: vec![proto::PhoneMetadata {
+                id: Some("001".into()),
+                country_code: Some(999),
+                general_desc: Some(proto::PhoneNumberDesc {
+                    national_number_pattern: Some(r"\d{6}".into()),
+                    possible_length: vec![6],
+                    ..Default::default()
+                }),
+                mobile: Some(proto::PhoneNumberDesc {
+                    possible_length: vec![-1],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+        }
+        .encode_to_vec();
+
+        let meta = proto::load(&encoded[..]).unwrap();
+        assert_eq!(Some(999), meta[0].country_code);
+        assert!(meta[0].mobile.is_none());
+        assert!(Database::from(meta).is_ok());
+
+        assert!(proto::load(&b"\x0a\xff"[..]).is_err());
+    }
+
+    #[test]
+    fn java() {
+        // The start of a Java serialized `PhoneMetadataCollection`.
+        let error = proto::load(&b"\xac\xed\x00\x05\x73\x72"[..]).unwrap_err();
+        assert!(error.to_string().contains("Java serialized"));
+    }
+}
@@ -0,0 +1,836 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use regex::RegexBuilder;
+use regex_syntax::hir::literal::{ExtractKind, Extractor};
+
+use crate::country;
+use crate::formatter::{self, Mode};
+use crate::metadata::diff::{Territory, TYPES};
+use crate::metadata::{Database, LazyRegex, Metadata};
+use crate::parser;
+use crate::parser::helper::Number as ParseNumber;
+use crate::phone_number::{PhoneNumber, Type};
+use crate::validator;
+
+/// A problem found in the metadata of a territory, see `Database::verify`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MetadataIssue {
+    /// A pattern does not compile.
+    InvalidPattern {
+        territory: Territory,
+        field: String,
+        pattern: String,
+        error: String,
+    },
+
+    /// Numbers starting with the prefix match the leading digits of the
+    /// territory and of another territory sharing the country code, which is
+    /// tried first.
+    LeadingDigitsShadowed {
+        territory: Territory,
+        prefix: String,
+        by: String,
+    },
+
+    /// An example number does not match the pattern of its descriptor.
+    ExampleMismatch {
+        territory: Territory,
+        kind: Type,
+        example: String,
+    },
+
+    /// An example number does not have one of the possible lengths of its
+    /// descriptor.
+    ExampleLength {
+        territory: Territory,
+        kind: Type,
+        example: String,
+    },
+
+    /// An example number cannot be parsed.
+    ExampleUnparsable {
+        territory: Territory,
+        kind: Type,
+        example: String,
+        error: String,
+    },
+
+    /// An example number is attributed to another territory sharing the
+    /// country code, which is tried first.
+    ExampleShadowed {
+        territory: Territory,
+        kind: Type,
+        example: String,
+        by: String,
+    },
+
+    /// No format applies to a valid example number.
+    ExampleUnformatted {
+        territory: Territory,
+        kind: Type,
+        example: String,
+    },
+
+    /// A formatted example number does not parse back to itself.
+    ExampleRoundTrip {
+        territory: Territory,
+        kind: Type,
+        example: String,
+        mode: Mode,
+        formatted: String,
+    },
+}
+
+impl fmt::Display for MetadataIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MetadataIssue::InvalidPattern {
+                territory,
+                field,
+                pattern,
+                error,
+            } => write!(
+                f,
+                "{}, {}: invalid pattern {:?}: {}",
+                territory, field, pattern, error
+            ),
+
+            MetadataIssue::LeadingDigitsShadowed {
+                territory,
+                prefix,
+                by,
+            } => write!(
+                f,
+                "{}: leading digits {} are attributed to {}",
+                territory, prefix, by
+            ),
+
+            MetadataIssue::ExampleMismatch {
+                territory,
+                kind,
+                example,
+            } => write!(
+                f,
+                "{}: {:?} example {} does not match its pattern",
+                territory, kind, example
+            ),
+
+            MetadataIssue::ExampleLength {
+                territory,
+                kind,
+                example,
+            } => write!(
+                f,
+                "{}: {:?} example {} does not have a possible length",
+                territory, kind, example
+            ),
+
+            MetadataIssue::ExampleUnparsable {
+                territory,
+                kind,
+                example,
+                error,
+            } => write!(
+                f,
+                "{}: {:?} example {} does not parse: {}",
+                territory, kind, example, error
+            ),
+
+            MetadataIssue::ExampleShadowed {
+                territory,
+                kind,
+                example,
+                by,
+            } => write!(
+                f,
+                "{}: {:?} example {} is attributed to {}",
+                territory, kind, example, by
+            ),
+
+            MetadataIssue::ExampleUnformatted {
+                territory,
+                kind,
+                example,
+            } => write!(
+                f,
+                "{}: no format for {:?} example {}",
+                territory, kind, example
+            ),
+
+            MetadataIssue::ExampleRoundTrip {
+                territory,
+                kind,
+                example,
+                mode,
+                formatted,
+            } => write!(
+                f,
+                "{}: {:?} example {} formatted as {:?} parses differently: {}",
+                territory, kind, example, mode, formatted
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Territory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (+{})", self.id, self.country_code)
+    }
+}
+
+/// Check the metadata of every territory in the database.
+pub(crate) fn verify(database: &Database) -> Vec<MetadataIssue> {
+    let mut territories = database.regions().collect::<Vec<_>>();
+    territories.sort_by(|a, b| (a.country_code, &a.id).cmp(&(b.country_code, &b.id)));
+
+    let mut issues = Vec::new();
+
+    for meta in territories {
+        let territory = Territory {
+            id: meta.id.to_string(),
+            country_code: meta.country_code,
+        };
+
+        patterns(&mut issues, &territory, meta);
+        leading_digits(&mut issues, database, &territory, meta);
+
+        for kind in TYPES {
+            let desc = match meta.descriptors.get(kind) {
+                Some(desc) => desc,
+                None => continue,
+            };
+
+            let example = match desc.example.as_ref() {
+                Some(example) => example,
+                None => continue,
+            };
+
+            if !is_full_match(&desc.national_number, example) {
+                issues.push(MetadataIssue::ExampleMismatch {
+                    territory: territory.clone(),
+                    kind,
+                    example: example.to_string(),
+                });
+            }
+
+            let number = ParseNumber {
+                national: example.as_ref().into(),
+                ..Default::default()
+            };
+
+            if !validator::length(meta, &number, kind).is_possible() {
+                issues.push(MetadataIssue::ExampleLength {
+                    territory: territory.clone(),
+                    kind,
+                    example: example.to_string(),
+                });
+            }
+
+            // Numbers are never classified as these.
+            if kind == Type::Unknown || kind == Type::NoInternational {
+                continue;
+            }
+
+            example_number(&mut issues, database, &territory, meta, kind, example);
+        }
+    }
+
+    issues
+}
+
+/// Check the whole string matches the pattern, with any of its alternatives
+/// rather than only the one the leftmost-first match takes.
+fn is_full_match(pattern: &LazyRegex, value: &str) -> bool {
+    RegexBuilder::new(&format!("^(?:{})$", pattern.source()))
+        .ignore_whitespace(true)
+        .build()
+        .map(|regex| regex.is_match(value))
+        .unwrap_or(false)
+}
+
+fn patterns(issues: &mut Vec<MetadataIssue>, territory: &Territory, meta: &Metadata) {
+    let desc = &meta.descriptors;
+    let descriptors = [
+        ("generalDesc", Some(&desc.general)),
+        ("fixedLine", desc.fixed_line.as_ref()),
+        ("mobile", desc.mobile.as_ref()),
+        ("tollFree", desc.toll_free.as_ref()),
+        ("premiumRate", desc.premium_rate.as_ref()),
+        ("sharedCost", desc.shared_cost.as_ref()),
+        ("personalNumber", desc.personal_number.as_ref()),
+        ("voip", desc.voip.as_ref()),
+        ("pager", desc.pager.as_ref()),
+        ("uan", desc.uan.as_ref()),
+        ("emergency", desc.emergency.as_ref()),
+        ("voicemail", desc.voicemail.as_ref()),
+        ("shortCode", desc.short_code.as_ref()),
+        ("standardRate", desc.standard_rate.as_ref()),
+        ("carrierSpecific", desc.carrier.as_ref()),
+        ("noInternationalDialling", desc.no_international.as_ref()),
+    ];
+
+    let mut patterns = Vec::<(String, &LazyRegex)>::new();
+
+    for (name, desc) in descriptors {
+        if let Some(desc) = desc {
+            patterns.push((
+                format!("{}/nationalNumberPattern", name),
+                &desc.national_number,
+            ));
+        }
+    }
+
+    for format in meta.formats.iter().chain(&meta.international_formats) {
+        patterns.push((
+            "availableFormats/numberFormat/pattern".into(),
+            &format.pattern,
+        ));
+
+        for pattern in &format.leading_digits {
+            patterns.push((
+                "availableFormats/numberFormat/leadingDigits".into(),
+                pattern,
+            ));
+        }
+    }
+
+    for (name, pattern) in [
+        ("internationalPrefix", meta.international_prefix.as_ref()),
+        (
+            "nationalPrefixForParsing",
+            meta.national_prefix_for_parsing.as_ref(),
+        ),
+        ("leadingDigits", meta.leading_digits.as_ref()),
+    ] {
+        patterns.extend(pattern.map(|r| (name.to_string(), r)));
+    }
+
+    for (field, pattern) in patterns {
+        if let Err(error) = pattern.try_regex() {
+            issues.push(MetadataIssue::InvalidPattern {
+                territory: territory.clone(),
+                field,
+                pattern: pattern.source().into(),
+                error: error.to_string(),
+            });
+        }
+    }
+}
+
+fn leading_digits(
+    issues: &mut Vec<MetadataIssue>,
+    database: &Database,
+    territory: &Territory,
+    meta: &Metadata,
+) {
+    let own = match meta.leading_digits.as_ref().and_then(prefixes) {
+        Some(own) => own,
+        None => return,
+    };
+
+    // Regions sharing a country code are tried in order, so only the ones
+    // before this one can take its numbers.
+    for other in database
+        .by_code(&meta.country_code)
+        .unwrap_or_default()
+        .into_iter()
+        .take_while(|other| !std::ptr::eq(*other, meta))
+    {
+        let theirs = match other.leading_digits.as_ref().and_then(prefixes) {
+            Some(theirs) => theirs,
+            None => continue,
+        };
+
+        // Numbers starting with the longer of two prefixes match both.
+        let overlap = own.iter().find_map(|a| {
+            theirs.iter().find_map(|b| {
+                if a.starts_with(b.as_str()) {
+                    Some(a)
+                } else if b.starts_with(a.as_str()) {
+                    Some(b)
+                } else {
+                    None
+                }
+            })
+        });
+
+        if let Some(prefix) = overlap {
+            issues.push(MetadataIssue::LeadingDigitsShadowed {
+                territory: territory.clone(),
+                prefix: prefix.clone(),
+                by: other.id.to_string(),
+            });
+        }
+    }
+}
+
+/// The digits the numbers matching the leading digits start with, if they
+/// are a short enough list.
+fn prefixes(pattern: &LazyRegex) -> Option<Vec<String>> {
+    let hir = regex_syntax::ParserBuilder::new()
+        .ignore_whitespace(true)
+        .unicode(false)
+        .build()
+        .parse(pattern.source())
+        .ok()?;
+
+    Extractor::new()
+        .kind(ExtractKind::Prefix)
+        .extract(&hir)
+        .literals()?
+        .iter()
+        .map(|literal| String::from_utf8(literal.as_bytes().to_vec()).ok())
+        .collect()
+}
+
+fn example_number(
+    issues: &mut Vec<MetadataIssue>,
+    database: &Database,
+    territory: &Territory,
+    meta: &Metadata,
+    kind: Type,
+    example: &str,
+) {
+    let region = meta.id.parse::<country::Id>().ok();
+    let parsed = match region {
+        Some(region) => parser::parse_with(database, Some(region), example),
+        None => parser::parse_with(database, None, format!("+{}{}", meta.country_code, example)),
+    };
+
+    let number = match parsed {
+        Ok(number) => number,
+        Err(error) => {
+            issues.push(MetadataIssue::ExampleUnparsable {
+                territory: territory.clone(),
+                kind,
+                example: example.into(),
+                error: error.to_string(),
+            });
+
+            return;
+        }
+    };
+
+    if !number.is_valid_with(database) {
+        return;
+    }
+
+    // Regions sharing a country code are tried in order, and the first one
+    // claiming the number is the one it is validated against.
+    let by = validator::metadata_for(database, meta.country_code, example)
+        .filter(|other| !std::ptr::eq(*other, meta));
+
+    if let Some(by) = by {
+        issues.push(MetadataIssue::ExampleShadowed {
+            territory: territory.clone(),
+            kind,
+            example: example.into(),
+            by: by.id.to_string(),
+        });
+    }
+
+    // Numbers are formatted with the metadata of the main country for the code.
+    let formats = database
+        .by_code(&meta.country_code)
+        .and_then(|m| m.into_iter().next())
+        .map(|m| m.formats())
+        .unwrap_or_default();
+
+    if !formats.is_empty() && formatter::formatter(example, formats).is_none() {
+        issues.push(MetadataIssue::ExampleUnformatted {
+            territory: territory.clone(),
+            kind,
+            example: example.into(),
+        });
+    }
+
+    let same = |other: &PhoneNumber| {
+        other.code().value() == number.code().value() && other.national() == number.national()
+    };
+
+    for mode in [
+        Mode::E164,
+        Mode::International,
+        Mode::National,
+        Mode::Rfc3966,
+    ] {
+        let formatted = number.format_with(database).mode(mode).to_string();
+        let reparsed = match (mode, region) {
+            (Mode::National, Some(region)) => {
+                parser::parse_with(database, Some(region), &formatted)
+            }
+            (Mode::National, None) => continue,
+            _ => parser::parse_with(database, None, &formatted),
+        };
+
+        if !reparsed.as_ref().map(same).unwrap_or(false) {
+            issues.push(MetadataIssue::ExampleRoundTrip {
+                territory: territory.clone(),
+                kind,
+                example: example.into(),
+                mode,
+                formatted,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::metadata::diff::Territory;
+    use crate::metadata::{Database, MetadataIssue, DEFAULT as DATABASE};
+    use crate::phone_number::Type;
+
+    #[test]
+    fn verify() {
+        let database = Database::parse(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="FR" countryCode="33" mainCountryForCode="true"
+                    leadingDigits="[1-8]" internationalPrefix="00">
+                    <availableFormats>
+                        <numberFormat pattern="(\d)(\d{2})(\d{2})(\d{2})(\d{2})">
+                            <leadingDigits>[1-79]</leadingDigits>
+                            <format>$1 $2 $3 $4 $5</format>
+                        </numberFormat>
+                    </availableFormats>
+                    <generalDesc>
+                        <nationalNumberPattern>[1-9]\d{8}</nationalNumberPattern>
+                    </generalDesc>
+                    <fixedLine>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>123456789</exampleNumber>
+                        <nationalNumberPattern>[1-5]\d{8}</nationalNumberPattern>
+                    </fixedLine>
+                    <mobile>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>512345678</exampleNumber>
+                        <nationalNumberPattern>[67]\d{8}</nationalNumberPattern>
+                    </mobile>
+                    <tollFree>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>80012345</exampleNumber>
+                        <nationalNumberPattern>80\d{6}</nationalNumberPattern>
+                    </tollFree>
+                    <premiumRate>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>812345678</exampleNumber>
+                        <nationalNumberPattern>8[1-9]\d{7}</nationalNumberPattern>
+                    </premiumRate>
+                </territory>
+                <territory id="MC" countryCode="33" leadingDigits="9">
+                    <generalDesc>
+                        <nationalNumberPattern>[69]\d{8}</nationalNumberPattern>
+                    </generalDesc>
+                    <mobile>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>612345678</exampleNumber>
+                        <nationalNumberPattern>6\d{8}</nationalNumberPattern>
+                    </mobile>
+                    <voip>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>912345678</exampleNumber>
+                        <nationalNumberPattern>9\d{8}</nationalNumberPattern>
+                    </voip>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        )
+        .unwrap();
+
+        let fr = Territory {
+            id: "FR".into(),
+            country_code: 33,
+        };
+
+        let mc = Territory {
+            id: "MC".into(),
+            country_code: 33,
+        };
+
+        assert_eq!(
+            vec![
+                MetadataIssue::ExampleMismatch {
+                    territory: fr.clone(),
+                    kind: Type::Mobile,
+                    example: "512345678".into(),
+                },
+                MetadataIssue::ExampleLength {
+                    territory: fr.clone(),
+                    kind: Type::TollFree,
+                    example: "80012345".into(),
+                },
+                MetadataIssue::ExampleUnformatted {
+                    territory: fr,
+                    kind: Type::PremiumRate,
+                    example: "812345678".into(),
+                },
+                MetadataIssue::ExampleShadowed {
+                    territory: mc,
+                    kind: Type::Mobile,
+                    example: "612345678".into(),
+                    by: "FR".into(),
+                },
+            ],
+            database.verify()
+        );
+    }
+
+    #[test]
+    fn lengths() {
+        // Without lengths of its own, a type has the lengths of every type, as
+        // in validator::length.
+        let database = Database::parse(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="BE" countryCode="32">
+                    <generalDesc>
+                        <nationalNumberPattern>[248]\d{8,9}</nationalNumberPattern>
+                    </generalDesc>
+                    <fixedLine>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>212345678</exampleNumber>
+                        <nationalNumberPattern>2\d{8}</nationalNumberPattern>
+                    </fixedLine>
+                    <mobile>
+                        <exampleNumber>4701234567</exampleNumber>
+                        <nationalNumberPattern>4\d{9}</nationalNumberPattern>
+                    </mobile>
+                    <tollFree>
+                        <possibleLengths national="10"/>
+                        <exampleNumber>8012345678</exampleNumber>
+                        <nationalNumberPattern>8\d{9}</nationalNumberPattern>
+                    </tollFree>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(Vec::<MetadataIssue>::new(), database.verify());
+    }
+
+    #[test]
+    fn full_match() {
+        // The first alternative only matches the start of the example.
+        let database = Database::parse(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="BE" countryCode="32">
+                    <generalDesc>
+                        <nationalNumberPattern>\d{8,9}</nationalNumberPattern>
+                    </generalDesc>
+                    <fixedLine>
+                        <possibleLengths national="8,9"/>
+                        <exampleNumber>212345678</exampleNumber>
+                        <nationalNumberPattern>2\d{7}|2\d{8}</nationalNumberPattern>
+                    </fixedLine>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(Vec::<MetadataIssue>::new(), database.verify());
+    }
+
+    #[test]
+    fn shadowed() {
+        // Without leading digits, the first region the number has a type in
+        // claims it.
+        let database = Database::parse(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="FR" countryCode="33" mainCountryForCode="true">
+                    <generalDesc>
+                        <nationalNumberPattern>[1-9]\d{8}</nationalNumberPattern>
+                    </generalDesc>
+                    <fixedLine>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>123456789</exampleNumber>
+                        <nationalNumberPattern>[1-5]\d{8}</nationalNumberPattern>
+                    </fixedLine>
+                </territory>
+                <territory id="MC" countryCode="33">
+                    <generalDesc>
+                        <nationalNumberPattern>[46]\d{8}</nationalNumberPattern>
+                    </generalDesc>
+                    <fixedLine>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>412345678</exampleNumber>
+                        <nationalNumberPattern>4\d{8}</nationalNumberPattern>
+                    </fixedLine>
+                    <mobile>
+                        <possibleLengths national="9"/>
+                        <exampleNumber>612345678</exampleNumber>
+                        <nationalNumberPattern>6\d{8}</nationalNumberPattern>
+                    </mobile>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![MetadataIssue::ExampleShadowed {
+                territory: Territory {
+                    id: "MC".into(),
+                    country_code: 33,
+                },
+                kind: Type::FixedLine,
+                example: "412345678".into(),
+                by: "FR".into(),
+            }],
+            database.verify()
+        );
+    }
+
+    #[test]
+    fn leading_digits() {
+        let database = Database::parse(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="NO" countryCode="47" mainCountryForCode="true"
+                    leadingDigits="[02-689]|7[0-8]">
+                    <generalDesc>
+                        <nationalNumberPattern>\d{8}</nationalNumberPattern>
+                    </generalDesc>
+                </territory>
+                <territory id="SJ" countryCode="47" leadingDigits="79">
+                    <generalDesc>
+                        <nationalNumberPattern>\d{8}</nationalNumberPattern>
+                    </generalDesc>
+                </territory>
+                <territory id="XX" countryCode="47" leadingDigits="7">
+                    <generalDesc>
+                        <nationalNumberPattern>\d{8}</nationalNumberPattern>
+                    </generalDesc>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        )
+        .unwrap();
+
+        let xx = Territory {
+            id: "XX".into(),
+            country_code: 47,
+        };
+
+        assert_eq!(
+            vec![
+                MetadataIssue::LeadingDigitsShadowed {
+                    territory: xx.clone(),
+                    prefix: "70".into(),
+                    by: "NO".into(),
+                },
+                MetadataIssue::LeadingDigitsShadowed {
+                    territory: xx,
+                    prefix: "79".into(),
+                    by: "SJ".into(),
+                },
+            ],
+            database.verify()
+        );
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let database = Database::parse(
+            r#"
+            <phoneNumberMetadata><territories>
+                <territory id="BE" countryCode="32">
+                    <generalDesc>
+                        <nationalNumberPattern>\d{8,9}</nationalNumberPattern>
+                    </generalDesc>
+                    <fixedLine>
+                        <nationalNumberPattern>(?:\d{1000}){1000}</nationalNumberPattern>
+                    </fixedLine>
+                </territory>
+            </territories></phoneNumberMetadata>
+        "#,
+        )
+        .unwrap();
+
+        let issues = database.verify();
+        assert_eq!(1, issues.len());
+
+        match &issues[0] {
+            MetadataIssue::InvalidPattern {
+                territory, field, ..
+            } => {
+                assert_eq!("BE", territory.id);
+                assert_eq!("fixedLine/nationalNumberPattern", field);
+            }
+
+            issue => panic!("unexpected issue: {}", issue),
+        }
+    }
+
+    #[test]
+    fn embedded() {
+        let mut shadowed = BTreeSet::new();
+        let mut unformatted = BTreeSet::new();
+
+        for issue in DATABASE.verify() {
+            match issue {
+                // Regions sharing a country code with their main country, by
+                // the region their examples are attributed to.
+                MetadataIssue::ExampleShadowed { territory, by, .. } => {
+                    shadowed.insert(format!("{} {}", territory.id, by));
+                }
+
+                // Numbers no format of the metadata applies to, which are kept
+                // as is.
+                MetadataIssue::ExampleUnformatted {
+                    territory, example, ..
+                } => {
+                    unformatted.insert(format!("{} {}", territory.id, example));
+                }
+
+                issue => panic!("unexpected issue: {}", issue),
+            }
+        }
+
+        let expected = [
+            "AG US", "AI US", "AS US", "AX FI", "BB US", "BL GP", "BM US", "BS US", "CA US",
+            "CC AU", "CX AU", "DM US", "DO US", "EH MA", "GD US", "GG GB", "GU US", "IM GB",
+            "JE GB", "JM US", "KN US", "KY US", "KZ RU", "LC US", "MF BL", "MF GP", "MP US",
+            "MS US", "PR US", "SJ NO", "SX US", "TC US", "TT US", "VA IT", "VC US", "VG US",
+            "VI US", "YT RE",
+        ];
+        assert_eq!(BTreeSet::from(expected.map(String::from)), shadowed);
+
+        let expected = [
+            "DE 16412345",
+            "GI 57123456",
+            "NO 02000",
+            "NU 7012",
+            "SB 18123",
+            "SB 40123",
+            "SB 51123",
+            "SC 8000000",
+            "SJ 02000",
+            "VU 22123",
+            "VU 30123",
+            "VU 81123",
+            "ZM 630123456",
+        ];
+        assert_eq!(BTreeSet::from(expected.map(String::from)), unformatted);
+    }
+}
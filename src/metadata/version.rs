@@ -0,0 +1,115 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+/// Where the metadata of a database comes from.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Version {
+    pub(crate) release: Option<String>,
+    pub(crate) source: Option<String>,
+    pub(crate) label: Option<String>,
+}
+
+impl Version {
+    /// The version of the embedded metadata.
+    pub(crate) fn embedded() -> Self {
+        Version {
+            release: option_env!("PHONENUMBER_METADATA_RELEASE").map(Into::into),
+            source: option_env!("PHONENUMBER_METADATA_SOURCE").map(Into::into),
+            label: option_env!("PHONENUMBER_METADATA_VERSION").map(Into::into),
+        }
+    }
+
+    /// The libphonenumber release the metadata was taken from, e.g. `8.13.9`.
+    ///
+    /// This is not set for databases loaded at runtime, or built from a custom
+    /// `PHONENUMBER_METADATA_XML`.
+    pub fn release(&self) -> Option<&str> {
+        self.release.as_deref()
+    }
+
+    /// The source the metadata was taken from, either the libphonenumber git
+    /// reference, e.g. `google/libphonenumber@95dd52a…`, or the path given in
+    /// `PHONENUMBER_METADATA_XML`.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// A custom label, as given with `Database::with_label` or in
+    /// `PHONENUMBER_METADATA_VERSION` at build time.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.label(), self.release()) {
+            (Some(label), _) => f.write_str(label)?,
+            (None, Some(release)) => write!(f, "libphonenumber {}", release)?,
+            (None, None) => f.write_str("unknown")?,
+        }
+
+        if let Some(source) = self.source() {
+            write!(f, " ({})", source)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::metadata::{Database, DEFAULT as DATABASE};
+
+    #[test]
+    fn embedded() {
+        let version = DATABASE.version();
+
+        assert_eq!(Some("8.13.9"), version.release());
+        assert!(version
+            .source()
+            .unwrap()
+            .starts_with("google/libphonenumber@"));
+        assert!(version
+            .to_string()
+            .starts_with("libphonenumber 8.13.9 (google/libphonenumber@"));
+    }
+
+    #[test]
+    fn label() {
+        let database = Database::parse(
+            r#"<phoneNumberMetadata><territories></territories></phoneNumberMetadata>"#,
+        )
+        .unwrap();
+
+        assert_eq!(None, database.version().release());
+        assert_eq!("unknown", database.version().to_string());
+
+        let database = database.with_label("2023-04-01");
+        assert_eq!(Some("2023-04-01"), database.version().label());
+        assert_eq!("2023-04-01", database.version().to_string());
+
+        let patched = DATABASE
+            .with_overrides(vec![])
+            .unwrap()
+            .with_label("patched");
+        assert_eq!(Some("8.13.9"), patched.version().release());
+        assert!(patched
+            .version()
+            .to_string()
+            .starts_with("patched (google/"));
+    }
+}
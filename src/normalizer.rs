@@ -0,0 +1,98 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::borrow::Cow;
+
+use crate::consts;
+use crate::parser::helper;
+
+/// Normalize a string to its digits only, converting any Unicode decimal
+/// digits (e.g. Arabic-Indic or full-width) into their ASCII counterpart and
+/// dropping everything else.
+pub fn normalize_digits_only<S: AsRef<str>>(string: S) -> String {
+    helper::normalize_str(Cow::Borrowed(string.as_ref()), &consts::ASCII_MAPPINGS).into_owned()
+}
+
+/// Normalize a string to the characters that are essential when dialling,
+/// that is digits, "+", "*" and "#", dropping everything else.
+pub fn normalize_diallable_chars_only<S: AsRef<str>>(string: S) -> String {
+    helper::normalize_str(
+        Cow::Borrowed(string.as_ref()),
+        &consts::DIALLABLE_CHAR_MAPPINGS,
+    )
+    .into_owned()
+}
+
+/// Normalize a string the same way the parser does.
+///
+/// If the string looks like a vanity number (it has at least three letters in
+/// it), letters are converted to their keypad digits, otherwise they are
+/// dropped like any other punctuation. Unicode decimal digits are always
+/// converted into their ASCII counterpart.
+pub fn normalize<S: AsRef<str>>(string: S) -> String {
+    let string = string.as_ref();
+
+    if consts::VALID_ALPHA_PHONE.is_match(string) {
+        helper::normalize_str(Cow::Borrowed(string), &consts::ALPHA_PHONE_MAPPINGS).into_owned()
+    } else {
+        normalize_digits_only(string)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::normalizer;
+
+    #[test]
+    fn normalize_digits_only() {
+        assert_eq!(
+            "03456234",
+            normalizer::normalize_digits_only("034-56&+a#234")
+        );
+        assert_eq!("420", normalizer::normalize_digits_only("４2０"));
+        assert_eq!("0123", normalizer::normalize_digits_only("٠١٢٣"));
+        assert_eq!("0123", normalizer::normalize_digits_only("۰۱۲۳"));
+        // Myanmar, Khmer, Tibetan and Mongolian.
+        assert_eq!("0129", normalizer::normalize_digits_only("၀၁၂၉"));
+        assert_eq!("0129", normalizer::normalize_digits_only("០១២៩"));
+        assert_eq!("0129", normalizer::normalize_digits_only("༠༡༢༩"));
+        assert_eq!("0129", normalizer::normalize_digits_only("᠐᠑᠒᠙"));
+        // Digits outside the Basic Multilingual Plane.
+        assert_eq!(
+            "09",
+            normalizer::normalize_digits_only("\u{1D7CE}\u{1D7E1}")
+        );
+    }
+
+    #[test]
+    fn normalize_diallable_chars_only() {
+        assert_eq!(
+            "03*456+1#234",
+            normalizer::normalize_diallable_chars_only("03*4-56&+1a#234")
+        );
+    }
+
+    #[test]
+    fn normalize() {
+        // Strips symbols.
+        assert_eq!("034562", normalizer::normalize("034-56&+#2\u{AD}"));
+        // Converts letters to numbers.
+        assert_eq!("034426486479", normalizer::normalize("034-I-am-HUNGRY"));
+        // Drops letters when there are too few of them.
+        assert_eq!("0344", normalizer::normalize("034-I-4"));
+        // Handles wide and Arabic-Indic digits.
+        assert_eq!("420", normalizer::normalize("４2０"));
+        assert_eq!("5123", normalizer::normalize("٥١٢٣"));
+    }
+}
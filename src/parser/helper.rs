@@ -22,8 +22,8 @@ use nom::{
 };
 use std::borrow::Cow;
 
+use crate::metadata::LazyRegex;
 use fnv::FnvHashMap;
-use regex_cache::CachedRegex;
 
 use crate::consts;
 use crate::country;
@@ -136,6 +136,13 @@ pub fn extract(value: &str) -> IResult<&str, &str> {
     }
 }
 
+/// Get the digits of an extension matched by `consts::EXTN_PATTERN`, which
+/// are in the first capture group that took part in the match, depending on
+/// the alternative that matched.
+pub fn extension<'a>(captures: &regex::Captures<'a>) -> Option<regex::Match<'a>> {
+    captures.iter().skip(1).flatten().next()
+}
+
 /// Parse and insert the proper country code.
 pub fn country_code<'a>(
     database: &Database,
@@ -157,8 +164,8 @@ pub fn country_code<'a>(
             }
 
             // If the prefix was already extracted, check it is valid.
-            if number.prefix.is_some() {
-                let prefix = number.prefix.as_ref().unwrap().parse()?;
+            if let Some(prefix) = number.prefix.as_ref() {
+                let prefix = prefix.parse()?;
 
                 if database.by_code(&prefix).is_none() {
                     return Err(error::Parse::InvalidCountryCode);
@@ -187,16 +194,27 @@ pub fn country_code<'a>(
         }
 
         country::Source::Default => {
-            if let Some(country) = country {
-                let meta = database.by_id(country.as_ref()).unwrap();
+            // The default country may have been left out of the database.
+            if let Some(meta) = country.and_then(|c| database.by_id(c.as_ref())) {
                 let code = meta.country_code.to_string();
 
-                if number.national.starts_with(&code)
-                    && (!meta.descriptors().general().is_match(&number.national)
-                        || !validator::length(meta, &number, Type::Unknown).is_possible())
-                {
-                    number.country = country::Source::Number;
-                    number.national = trim(number.national, code.len());
+                // Like libphonenumber, only take the country code off when the
+                // rest is a better match, or the whole is too long.
+                if number.national.starts_with(&code) {
+                    let general = meta.descriptors().general().national_number();
+                    let matches = |value: &str| general.match_at_start(value) == Some(value.len());
+
+                    let mut potential = number.clone();
+                    potential.national = trim(potential.national, code.len());
+                    let potential = national_number(meta, potential);
+
+                    if (!matches(&number.national) && matches(&potential.national))
+                        || validator::length(meta, &number, Type::Unknown)
+                            == validator::Validation::TooLong
+                    {
+                        number.country = country::Source::Number;
+                        number.national = trim(number.national, code.len());
+                    }
                 }
 
                 number.prefix = Some(code.into());
@@ -214,7 +232,7 @@ pub fn country_code<'a>(
 ///
 /// Note that since the IDD comes from a passed default region, we can find the
 /// country code from the given default if the country source is from the IDD.
-pub fn international_prefix<'a>(idd: Option<&CachedRegex>, mut number: Number<'a>) -> Number<'a> {
+pub fn international_prefix<'a>(idd: Option<&LazyRegex>, mut number: Number<'a>) -> Number<'a> {
     // If there's a prefix already, i.e. RFC3966, just change the country source.
     if number.prefix.is_some() {
         number.country = country::Source::Plus;
@@ -232,10 +250,9 @@ pub fn international_prefix<'a>(idd: Option<&CachedRegex>, mut number: Number<'a
         number.national = trim(number.national, start);
         number = normalize(number, &consts::ALPHA_PHONE_MAPPINGS);
 
-        if !idd
-            .and_then(|re| re.find(&number.national))
-            .map(|m| m.start() == 0)
-            .unwrap_or(false)
+        if idd
+            .and_then(|re| re.match_at_start(&number.national))
+            .is_none()
         {
             return number;
         }
@@ -244,16 +261,13 @@ pub fn international_prefix<'a>(idd: Option<&CachedRegex>, mut number: Number<'a
         number = normalize(number, &consts::ALPHA_PHONE_MAPPINGS);
     }
 
-    // Check if the IDD pattern matches.
-    let index = idd
-        .and_then(|re| re.find(&number.national))
-        .map(|m| (m.start(), m.end()));
+    // Check if the IDD pattern matches at the beginning.
+    let index = idd.and_then(|re| re.match_at_start(&number.national));
 
     // If it does.
-    if let Some((start, end)) = index {
-        // Check it starts at the beginning and the next digit after the IDD is not
-        // a 0, since that's invalid.
-        if start == 0 && !number.national[end..].starts_with('0') {
+    if let Some(end) = index {
+        // Check the next digit after the IDD is not a 0, since that's invalid.
+        if !number.national[end..].starts_with('0') {
             if number.country != country::Source::Plus {
                 number.country = country::Source::Idd;
             }
@@ -271,7 +285,7 @@ pub fn national_number<'a>(meta: &Metadata, mut number: Number<'a>) -> Number<'a
     let parsing = if let Some(re) = meta.national_prefix_for_parsing.as_ref() {
         re
     } else {
-        if let Some(prefix) = meta.national_prefix.as_ref() {
+        if let Some(prefix) = meta.national_prefix.as_deref() {
             if number.national.starts_with(prefix) {
                 number.national = trim(number.national, prefix.len());
             }
@@ -280,40 +294,29 @@ pub fn national_number<'a>(meta: &Metadata, mut number: Number<'a>) -> Number<'a
         return number;
     };
 
-    let index = parsing.find(&number.national).map(|m| (m.start(), m.end()));
-
-    if index.is_none() {
-        return number;
-    }
-
-    let (start, end) = index.unwrap();
-    if start != 0 {
+    let captures = if let Some(captures) = parsing.captures_at_start(&number.national) {
+        captures
+    } else {
         return number;
-    }
+    };
 
     let viable = meta.descriptors.general.is_match(&number.national);
-    let groups = parsing.captures_len();
-
-    let (first, last) = parsing
-        .captures(&number.national)
-        .map(|c| {
-            (
-                c.get(1).map(|m| m.as_str().to_owned()),
-                c.get(c.len() - 1).map(|m| m.as_str().to_owned()),
-            )
-        })
-        .unwrap();
+    let groups = captures.len();
+    let end = captures.end();
+
+    let first = captures.get(1).map(ToOwned::to_owned);
+    let last = captures.get(groups - 1).map(ToOwned::to_owned);
 
     if transform.is_none() || last.is_none() {
-        if viable && !meta.descriptors.general.is_match(&number.national[start..]) {
+        if viable && !meta.descriptors.general.is_match(&number.national) {
             return number;
         }
 
-        number.carrier = last.filter(|_| groups > 0).map(Into::into);
+        number.carrier = last.filter(|_| groups > 1).map(Into::into);
 
         number.national = trim(number.national, end);
     } else if let Some(transform) = transform {
-        let transformed = parsing.replace(&number.national, transform).into_owned();
+        let transformed = captures.replace(transform);
 
         if viable && !meta.descriptors.general.is_match(&transformed) {
             return number;
@@ -332,42 +335,47 @@ pub fn national_number<'a>(meta: &Metadata, mut number: Number<'a>) -> Number<'a
 ///
 /// Note if the `Number` is already normalized it does not get modified.
 pub fn normalize<'a>(mut number: Number<'a>, mappings: &FnvHashMap<char, char>) -> Number<'a> {
-    fn act<'a>(value: Cow<'a, str>, mappings: &FnvHashMap<char, char>) -> Cow<'a, str> {
-        let mut owned = None;
-        {
-            let mut chars = value.char_indices();
+    number.national = normalize_str(number.national, mappings);
+    number.prefix = number.prefix.map(|p| normalize_str(p, mappings));
+    number.extension = number.extension.map(|e| normalize_str(e, mappings));
 
-            while let Some((start, ch)) = chars.next() {
-                if !ch.is_dec_digit() {
-                    let mut string = String::from(&value[..start]);
+    number
+}
 
-                    if let Some(ch) = ch.as_dec_digit() {
+/// Normalize a string, replacing the characters matching the mappings,
+/// converting any Unicode decimal digits into their ASCII counterpart and
+/// dropping everything else.
+///
+/// Note if the string is already normalized it does not get modified.
+pub fn normalize_str<'a>(value: Cow<'a, str>, mappings: &FnvHashMap<char, char>) -> Cow<'a, str> {
+    let mut owned = None;
+    {
+        let mut chars = value.char_indices();
+
+        while let Some((start, ch)) = chars.next() {
+            if !ch.is_dec_digit() {
+                let mut string = String::from(&value[..start]);
+
+                if let Some(ch) = ch.to_dec_digit() {
+                    string.push(ch);
+                } else if let Some(&ch) = mappings.get(&ch) {
+                    string.push(ch);
+                }
+
+                for (_, ch) in chars.by_ref() {
+                    if let Some(ch) = ch.to_dec_digit() {
                         string.push(ch);
                     } else if let Some(&ch) = mappings.get(&ch) {
                         string.push(ch);
                     }
-
-                    for (_, ch) in chars.by_ref() {
-                        if let Some(ch) = ch.as_dec_digit() {
-                            string.push(ch);
-                        } else if let Some(&ch) = mappings.get(&ch) {
-                            string.push(ch);
-                        }
-                    }
-
-                    owned = Some(string);
                 }
+
+                owned = Some(string);
             }
         }
-
-        owned.map(Cow::Owned).unwrap_or(value)
     }
 
-    number.national = act(number.national, mappings);
-    number.prefix = number.prefix.map(|p| act(p, mappings));
-    number.extension = number.extension.map(|e| act(e, mappings));
-
-    number
+    owned.map(Cow::Owned).unwrap_or(value)
 }
 
 pub fn trim(value: Cow<str>, start: usize) -> Cow<str> {
@@ -381,93 +389,106 @@ pub fn trim(value: Cow<str>, start: usize) -> Cow<str> {
     }
 }
 
-#[allow(clippy::wrong_self_convention)]
 pub trait AsCharExt {
-    fn is_wide_digit(self) -> bool;
-    fn is_punctuation(self) -> bool;
-    fn is_plus(self) -> bool;
-    fn is_start(self) -> bool;
-    fn is_valid(self) -> bool;
-
-    fn as_dec_digit(self) -> Option<char>;
+    fn to_dec_digit(self) -> Option<char>;
 }
 
 impl<T: AsChar> AsCharExt for T {
-    fn is_wide_digit(self) -> bool {
-        self.as_char().is_ascii_digit()
-    }
-
-    fn is_punctuation(self) -> bool {
-        let ch = self.as_char();
-        "-x\u{2010}\u{2011}\u{2012}\u{2013}\u{2014}\u{2015}\u{2212}\u{30FC}\u{FF0D}-\u{FF0F} \u{00A0}\u{00AD}\u{200B}\u{2060}\u{3000}()\u{FF08}\u{FF09}\u{FF3B}\u{FF3D}[]/~\u{2053}\u{223C}\u{FF5E}"
-			.chars().any(|c| c == ch)
-    }
-
-    fn is_plus(self) -> bool {
-        let ch = self.as_char();
-        ch == '+' || ch == '\u{FF0B}'
-    }
-
-    fn is_start(self) -> bool {
-        let ch = self.as_char();
-        ch.is_wide_digit() || ch.is_dec_digit() || ch.is_plus()
-    }
-
-    fn is_valid(self) -> bool {
-        let ch = self.as_char();
-        ch.is_start() || ch.is_alpha() || ch.is_punctuation()
-    }
-
-    fn as_dec_digit(self) -> Option<char> {
+    fn to_dec_digit(self) -> Option<char> {
         let ch = self.as_char();
 
         if ch.is_dec_digit() {
             return Some(ch);
         }
 
-        match ch {
-            '٠' | '۰' | '߀' | '०' | '০' | '੦' | '૦' | '୦' | '௦' | '౦' | '೦' | '൦' | '๐' | '໐'
-            | '０' => Some('0'),
-
-            '١' | '۱' | '߁' | '१' | '১' | '੧' | '૧' | '୧' | '௧' | '౧' | '೧' | '൧' | '๑' | '໑'
-            | '１' => Some('1'),
+        // The digits of every script are contiguous, from zero to nine.
+        let index = DEC_DIGIT_ZEROS.partition_point(|&zero| zero <= ch);
+        let zero = *DEC_DIGIT_ZEROS.get(index.checked_sub(1)?)?;
 
-            '٢' | '۲' | '߂' | '२' | '২' | '੨' | '૨' | '୨' | '௨' | '౨' | '೨' | '൨' | '๒' | '໒'
-            | '２' => Some('2'),
-
-            '٣' | '۳' | '߃' | '३' | '৩' | '੩' | '૩' | '୩' | '௩' | '౩' | '೩' | '൩' | '๓' | '໓'
-            | '３' => Some('3'),
-
-            '٤' | '۴' | '߄' | '४' | '৪' | '੪' | '૪' | '୪' | '௪' | '౪' | '೪' | '൪' | '๔' | '໔'
-            | '４' => Some('4'),
-
-            '٥' | '۵' | '߅' | '५' | '৫' | '੫' | '૫' | '୫' | '௫' | '౫' | '೫' | '൫' | '๕' | '໕'
-            | '５' => Some('5'),
-
-            '٦' | '۶' | '߆' | '६' | '৬' | '੬' | '૬' | '୬' | '௬' | '౬' | '೬' | '൬' | '๖' | '໖'
-            | '６' => Some('6'),
-
-            '٧' | '۷' | '߇' | '७' | '৭' | '੭' | '૭' | '୭' | '௭' | '౭' | '೭' | '൭' | '๗' | '໗'
-            | '７' => Some('7'),
-
-            '٨' | '۸' | '߈' | '८' | '৮' | '੮' | '૮' | '୮' | '௮' | '౮' | '೮' | '൮' | '๘' | '໘'
-            | '８' => Some('8'),
-
-            '٩' | '۹' | '߉' | '९' | '৯' | '੯' | '૯' | '୯' | '௯' | '౯' | '೯' | '൯' | '๙' | '໙'
-            | '９' => Some('9'),
-
-            _ => None,
-        }
+        char::from_digit(ch as u32 - zero as u32, 10)
     }
 }
 
+/// The zero of every run of decimal digits (general category `Nd`) as of
+/// Unicode 15.0, sorted.
+const DEC_DIGIT_ZEROS: &[char] = &[
+    '\u{30}',
+    '\u{660}',
+    '\u{6F0}',
+    '\u{7C0}',
+    '\u{966}',
+    '\u{9E6}',
+    '\u{A66}',
+    '\u{AE6}',
+    '\u{B66}',
+    '\u{BE6}',
+    '\u{C66}',
+    '\u{CE6}',
+    '\u{D66}',
+    '\u{DE6}',
+    '\u{E50}',
+    '\u{ED0}',
+    '\u{F20}',
+    '\u{1040}',
+    '\u{1090}',
+    '\u{17E0}',
+    '\u{1810}',
+    '\u{1946}',
+    '\u{19D0}',
+    '\u{1A80}',
+    '\u{1A90}',
+    '\u{1B50}',
+    '\u{1BB0}',
+    '\u{1C40}',
+    '\u{1C50}',
+    '\u{A620}',
+    '\u{A8D0}',
+    '\u{A900}',
+    '\u{A9D0}',
+    '\u{A9F0}',
+    '\u{AA50}',
+    '\u{ABF0}',
+    '\u{FF10}',
+    '\u{104A0}',
+    '\u{10D30}',
+    '\u{11066}',
+    '\u{110F0}',
+    '\u{11136}',
+    '\u{111D0}',
+    '\u{112F0}',
+    '\u{11450}',
+    '\u{114D0}',
+    '\u{11650}',
+    '\u{116C0}',
+    '\u{11730}',
+    '\u{118E0}',
+    '\u{11950}',
+    '\u{11C50}',
+    '\u{11D50}',
+    '\u{11DA0}',
+    '\u{11F50}',
+    '\u{16A60}',
+    '\u{16AC0}',
+    '\u{16B50}',
+    '\u{1D7CE}',
+    '\u{1D7D8}',
+    '\u{1D7E2}',
+    '\u{1D7EC}',
+    '\u{1D7F6}',
+    '\u{1E140}',
+    '\u{1E2F0}',
+    '\u{1E4F0}',
+    '\u{1E950}',
+    '\u{1FBF0}',
+];
+
 #[cfg(test)]
 mod test {
-    use regex_cache::CachedRegex;
+    use crate::metadata::LazyRegex;
 
     use crate::consts;
     use crate::country;
-    use crate::metadata::DATABASE;
+    use crate::metadata::DEFAULT as DATABASE;
     use crate::parser::helper;
     use crate::parser::helper::*;
 
@@ -544,6 +565,15 @@ mod test {
             "650) 253-0000",
             helper::extract("(650) 253-0000\u{200F}").unwrap().1
         );
+        // A second extension is the start of another number.
+        assert_eq!(
+            "530) 583-6985 x302",
+            helper::extract("(530) 583-6985 x302/x2303").unwrap().1
+        );
+        assert_eq!(
+            "530) 583-6985 x302 ",
+            helper::extract("(530) 583-6985 x302 / x2303").unwrap().1
+        );
     }
 
     #[test]
@@ -732,7 +762,7 @@ mod test {
                 ..Default::default()
             },
             helper::international_prefix(
-                Some(&CachedRegex::new(DATABASE.cache(), "00[39]").unwrap()),
+                Some(&LazyRegex::new("00[39]").unwrap()),
                 Number {
                     national: "0034567700-3898003".into(),
 
@@ -749,7 +779,7 @@ mod test {
                 ..Default::default()
             },
             helper::international_prefix(
-                Some(&CachedRegex::new(DATABASE.cache(), "00[39]").unwrap()),
+                Some(&LazyRegex::new("00[39]").unwrap()),
                 Number {
                     national: "00945677003898003".into(),
 
@@ -766,7 +796,7 @@ mod test {
                 ..Default::default()
             },
             helper::international_prefix(
-                Some(&CachedRegex::new(DATABASE.cache(), "00[39]").unwrap()),
+                Some(&LazyRegex::new("00[39]").unwrap()),
                 Number {
                     national: "00 9 45677003898003".into(),
 
@@ -782,7 +812,7 @@ mod test {
                 ..Default::default()
             },
             helper::international_prefix(
-                Some(&CachedRegex::new(DATABASE.cache(), "00[39]").unwrap()),
+                Some(&LazyRegex::new("00[39]").unwrap()),
                 Number {
                     national: "45677003898003".into(),
 
@@ -799,7 +829,7 @@ mod test {
                 ..Default::default()
             },
             helper::international_prefix(
-                Some(&CachedRegex::new(DATABASE.cache(), "00[39]").unwrap()),
+                Some(&LazyRegex::new("00[39]").unwrap()),
                 Number {
                     national: "+45677003898003".into(),
 
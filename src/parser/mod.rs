@@ -17,7 +17,7 @@ use crate::consts;
 use crate::country;
 use crate::error;
 use crate::extension::Extension;
-use crate::metadata::{Database, DATABASE};
+use crate::metadata::Database;
 use crate::national_number::NationalNumber;
 use crate::phone_number::{PhoneNumber, Type};
 use crate::validator::{self, Validation};
@@ -35,7 +35,72 @@ pub fn parse<S: AsRef<str>>(
     country: Option<country::Id>,
     string: S,
 ) -> Result<PhoneNumber, error::Parse> {
-    parse_with(&DATABASE, country, string)
+    parse_with(&Database::snapshot(), country, string)
+}
+
+/// Extract the part of a string that may be a phone number.
+///
+/// Any leading characters that cannot start a phone number are stripped,
+/// except for an opening bracket the number closes, as in "(030) 1234-5678".
+/// If the string contains the start of a second number, such as the second
+/// extension in "(530) 583-6985 x302/x2303", or a list of numbers, only the
+/// first one is kept. Trailing labels such as "Fax" or "(home)" are dropped,
+/// as are trailing characters that are neither letters nor digits (except
+/// for "#", which may end an extension).
+///
+/// This differs from libphonenumber's `extractPossibleNumber`, which drops
+/// the opening bracket, only cuts at a second extension and keeps trailing
+/// labels, giving "030) 1234-5678 / Fax" for the example below.
+///
+/// # Example
+///
+/// ```
+/// use phonenumber;
+///
+/// assert_eq!(
+///     Some("(030) 1234-5678"),
+///     phonenumber::extract_possible_number("Tel: (030) 1234-5678 / Fax ...")
+/// );
+/// ```
+pub fn extract_possible_number(string: &str) -> Option<&str> {
+    let (_, number) = helper::extract(string).ok()?;
+    let start = number.as_ptr() as usize - string.as_ptr() as usize;
+
+    let number = match string[..start].chars().next_back() {
+        Some(open) if closes(number, open) => {
+            &string[start - open.len_utf8()..start + number.len()]
+        }
+
+        _ => number,
+    };
+
+    let mut number = entries(number)[0];
+
+    while let Some(end) = consts::UNWANTED_END_CHARS
+        .find(number)
+        .or_else(|| consts::TRAILING_LABEL.find(number))
+        .map(|m| m.start())
+    {
+        number = &number[..end];
+    }
+
+    Some(number).filter(|number| !number.is_empty())
+}
+
+/// Check if the number closes the given opening bracket before opening
+/// another one.
+fn closes(number: &str, open: char) -> bool {
+    let close = match open {
+        '(' => ')',
+        '[' => ']',
+        '\u{FF08}' => '\u{FF09}',
+        '\u{FF3B}' => '\u{FF3D}',
+        _ => return false,
+    };
+
+    number
+        .find(close)
+        .is_some_and(|i| !number[..i].contains(open))
 }
 
 /// Parse a phone number using a specific `Database`.
@@ -44,26 +109,108 @@ pub fn parse_with<S: AsRef<str>>(
     country: Option<country::Id>,
     string: S,
 ) -> Result<PhoneNumber, error::Parse> {
-    fn phone_number(i: &str) -> IResult<&str, helper::Number> {
+    let number = tokenize(string.as_ref())?;
+    let number = prepare(database, country, number)?;
+
+    build(number)
+}
+
+/// Parse a phone number trying each of the candidate countries in turn.
+///
+/// Only numbers that are valid for the country they end up in are returned,
+/// each with that country and its [`Type`]. Results are ranked so mobile and
+/// fixed-line numbers come before other types, with premium rate numbers
+/// last; ties keep the order of the candidates. The same number is only
+/// returned once.
+///
+/// # Example
+///
+/// ```
+/// use phonenumber::{self, country, Type};
+///
+/// let results = phonenumber::parse_with_candidates(&[country::US, country::GB], "07912 345678");
+/// let (id, _, kind) = &results[0];
+///
+/// assert_eq!(country::GB, *id);
+/// assert_eq!(Type::Mobile, *kind);
+/// ```
+pub fn parse_with_candidates<S: AsRef<str>>(
+    candidates: &[country::Id],
+    string: S,
+) -> Vec<(country::Id, PhoneNumber, Type)> {
+    parse_with_candidates_with(&Database::snapshot(), candidates, string)
+}
+
+/// Parse a phone number trying each of the candidate countries in turn, using
+/// a specific `Database`.
+pub fn parse_with_candidates_with<S: AsRef<str>>(
+    database: &Database,
+    candidates: &[country::Id],
+    string: S,
+) -> Vec<(country::Id, PhoneNumber, Type)> {
+    fn rank(kind: Type) -> u8 {
+        match kind {
+            Type::Mobile | Type::FixedLine | Type::FixedLineOrMobile => 0,
+            Type::PremiumRate => 2,
+            _ => 1,
+        }
+    }
+
+    let raw = match tokenize(string.as_ref()) {
+        Ok(raw) => raw,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut result = Vec::<(country::Id, PhoneNumber, Type)>::new();
+
+    for &candidate in candidates {
+        let number = match prepare(database, Some(candidate), raw.clone()).and_then(build) {
+            Ok(number) => number,
+            Err(_) => continue,
+        };
+
+        if !number.is_valid_with(database) || result.iter().any(|(_, other, _)| *other == number) {
+            continue;
+        }
+
+        let id = number
+            .metadata(database)
+            .and_then(|m| m.id().parse().ok())
+            .unwrap_or(candidate);
+
+        let kind = number.number_type(database);
+        result.push((id, number, kind));
+    }
+
+    // The sort is stable, so the candidate order breaks ties.
+    result.sort_by_key(|&(_, _, kind)| rank(kind));
+    result
+}
+
+/// Try to parse the number as RFC3966 or natural language.
+fn tokenize(string: &str) -> Result<helper::Number<'_>, error::Parse> {
+    fn phone_number(i: &str) -> IResult<&str, helper::Number<'_>> {
         parse! { i => alt((rfc3966::phone_number, natural::phone_number)) }
     }
 
-    // Try to parse the number as RFC3966 or natural language.
-    let (_, mut number) = phone_number(string.as_ref()).or(Err(error::Parse::NoNumber))?;
+    phone_number(string)
+        .map(|(_, number)| number)
+        .or(Err(error::Parse::NoNumber))
+}
 
+/// Extract the country code, carrier and national number for the given
+/// country.
+fn prepare<'a>(
+    database: &Database,
+    country: Option<country::Id>,
+    mut number: helper::Number<'a>,
+) -> Result<helper::Number<'a>, error::Parse> {
     // Normalize the number and extract country code.
     number = helper::country_code(database, country, number)?;
 
     // Extract carrier and strip national prefix if present.
     if let Some(meta) = country.and_then(|c| database.by_id(c.as_ref())) {
-        let mut potential = helper::national_number(meta, number.clone());
-
-        // Strip national prefix if present.
-        if let Some(prefix) = meta.national_prefix.as_ref() {
-            if potential.national.starts_with(prefix) {
-                potential.national = helper::trim(potential.national, prefix.len());
-            }
-        }
+        let potential = helper::national_number(meta, number.clone());
 
         if validator::length(meta, &potential, Type::Unknown) != Validation::TooShort {
             number = potential;
@@ -78,6 +225,11 @@ pub fn parse_with<S: AsRef<str>>(
         return Err(error::Parse::TooLong);
     }
 
+    Ok(number)
+}
+
+/// Build the final `PhoneNumber` from a prepared number.
+fn build(number: helper::Number<'_>) -> Result<PhoneNumber, error::Parse> {
     Ok(PhoneNumber {
         code: country::Code {
             value: number.prefix.map(|p| p.parse()).unwrap_or(Ok(0))?,
@@ -94,12 +246,144 @@ pub fn parse_with<S: AsRef<str>>(
     })
 }
 
+/// Parse every phone number in a list, such as a contact field containing
+/// "+1 650 253 0000, 650-253-0001 (home)".
+///
+/// Entries are split on commas, semicolons, pipes, line breaks and spaced
+/// slashes between two numbers, and trailing labels like "(home)" are dropped.
+/// Without a default country, the one detected from the first parsed number
+/// is used for any later entry written in national format. An entry
+/// introduced by a second extension, as in "(530) 583-6985 x302/x2303",
+/// reuses the previous number with the new extension.
+pub fn parse_all<S: AsRef<str>>(
+    country: Option<country::Id>,
+    string: S,
+) -> Vec<Result<PhoneNumber, error::Parse>> {
+    parse_all_with(&Database::snapshot(), country, string)
+}
+
+/// Parse every phone number in a list using a specific `Database`.
+pub fn parse_all_with<S: AsRef<str>>(
+    database: &Database,
+    mut country: Option<country::Id>,
+    string: S,
+) -> Vec<Result<PhoneNumber, error::Parse>> {
+    let mut result = Vec::new();
+    let mut previous: Option<PhoneNumber> = None;
+
+    for entry in entries(string.as_ref()) {
+        // Keep the "x" of a second extension so it is recognized as such.
+        let mut starts = consts::SECOND_NUMBER_START
+            .find_iter(entry)
+            .map(|m| m.end() - 1)
+            .collect::<Vec<_>>();
+        starts.insert(0, 0);
+        starts.push(entry.len());
+
+        for part in starts.windows(2).map(|w| &entry[w[0]..w[1]]) {
+            let part = consts::TRAILING_LABEL.replace(part, "");
+            let part = part.trim_matches(|c: char| c.is_whitespace() || c == '/' || c == '\\');
+
+            if part.is_empty() {
+                continue;
+            }
+
+            // An extension on its own belongs to the previous number.
+            if let Some(extension) = consts::EXTN_PATTERN
+                .captures(part)
+                .filter(|c| c.get(0).map(|m| m.start()) == Some(0))
+                .and_then(|c| helper::extension(&c))
+            {
+                result.push(
+                    previous
+                        .as_ref()
+                        .map(|number| PhoneNumber {
+                            extension: Some(Extension(extension.as_str().into())),
+                            ..number.clone()
+                        })
+                        .ok_or(error::Parse::NoNumber),
+                );
+
+                continue;
+            }
+
+            let number = parse_with(database, country, part);
+
+            if let Ok(number) = number.as_ref() {
+                // Only fall back to the first number's country, a default
+                // given by the caller is kept for the whole list.
+                if previous.is_none() && country.is_none() {
+                    country = number
+                        .metadata(database)
+                        .map(|m| m.id())
+                        .or_else(|| {
+                            database
+                                .region(&number.code().value())
+                                .and_then(|r| r.into_iter().next())
+                        })
+                        .and_then(|id| id.parse().ok());
+                }
+
+                previous = Some(number.clone());
+            }
+
+            result.push(number);
+        }
+    }
+
+    result
+}
+
+/// Split a list of numbers at its separators, except for a "," or ";" followed
+/// by an extension or RFC3966 parameter of the same number, and a spaced slash
+/// that does not follow a whole number or is not followed by any digits.
+fn entries(string: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = 0;
+
+    for separator in consts::NUMBER_LIST_SEPARATOR.find_iter(string) {
+        let rest = &string[separator.end()..];
+
+        if separator.as_str().chars().all(|c| c == ',' || c == ';')
+            && consts::NUMBER_LIST_CONTINUATION.is_match(rest)
+        {
+            continue;
+        }
+
+        if separator.as_str().contains(['/', '\\']) {
+            let digits = string[start..separator.start()]
+                .chars()
+                .filter(|c| c.is_numeric())
+                .count();
+            // The next entry may start with a separator of its own, as in ";ext=123".
+            let next = consts::NUMBER_LIST_SEPARATOR
+                .find_at(rest, rest.chars().next().map_or(0, char::len_utf8))
+                .map_or(rest, |m| &rest[..m.start()]);
+
+            if digits < consts::MIN_LENGTH_BEFORE_LIST_SLASH || !next.contains(char::is_numeric) {
+                continue;
+            }
+        }
+
+        result.push(&string[start..separator.start()]);
+        start = separator.end();
+    }
+
+    result.push(&string[start..]);
+    result
+}
+
 #[cfg(test)]
 mod test {
+    use std::fs::File;
+    use std::io::BufReader;
+
     use crate::country;
+    use crate::error;
+    use crate::metadata::{loader, Database};
     use crate::national_number::NationalNumber;
-    use crate::parser;
-    use crate::phone_number::PhoneNumber;
+    use crate::parser::{self, helper};
+    use crate::phone_number::{PhoneNumber, Type};
 
     #[test]
     fn parse() {
@@ -287,6 +571,301 @@ mod test {
         );
     }
 
+    #[test]
+    fn country_code_in_national_number() {
+        let parse = |country, number| {
+            let number = parser::parse(Some(country), number).unwrap();
+            (
+                number.code().source(),
+                number.national().to_string(),
+                crate::is_valid(&number),
+            )
+        };
+
+        // The country code is only taken off when the rest is a better match.
+        assert_eq!(
+            (country::Source::Number, "301234567".into(), true),
+            parse(country::DE, "49 30 1234567")
+        );
+        assert_eq!(
+            (country::Source::Number, "9876543210".into(), true),
+            parse(country::IN, "919876543210")
+        );
+
+        // National numbers starting with the country code are kept whole.
+        assert_eq!(
+            (country::Source::Default, "4941123456".into(), true),
+            parse(country::DE, "4941 123456")
+        );
+        assert_eq!(
+            (country::Source::Default, "49613112345".into(), true),
+            parse(country::DE, "4961 3112345")
+        );
+        assert_eq!(
+            (country::Source::Default, "9123456789".into(), true),
+            parse(country::IN, "9123456789")
+        );
+        assert_eq!(
+            (country::Source::Default, "9198765432".into(), true),
+            parse(country::IN, "9198765432")
+        );
+    }
+
+    #[test]
+    fn national_prefix() {
+        let parse = |country, number| {
+            let number = parser::parse(Some(country), number).unwrap();
+            (number.national().to_string(), number.carrier().cloned())
+        };
+
+        // The national prefix is only stripped once, even when the number
+        // starts with the same digit.
+        assert_eq!(
+            ("8001234567".into(), None),
+            parse(country::RU, "8 (800) 123-45-67")
+        );
+        assert_eq!(
+            ("4951234567".into(), None),
+            parse(country::RU, "8 (495) 123-45-67")
+        );
+        assert_eq!(
+            ("80012345".into(), None),
+            parse(country::LT, "8 800 12 345")
+        );
+
+        // A national prefix without groups is not a carrier code.
+        assert_eq!(("61234567".into(), None), parse(country::LT, "8 612 34567"));
+        assert_eq!(
+            ("173271234".into(), None),
+            parse(country::BY, "8 017 327 12 34")
+        );
+        assert_eq!(
+            ("2155555555".into(), Some("15".into())),
+            parse(country::BR, "0 15 21 5555-5555")
+        );
+    }
+
+    #[test]
+    fn international_with_separators() {
+        let number = parser::parse(None, "+1 650-253-0000").unwrap();
+
+        assert_eq!(1, number.code().value());
+        assert_eq!(6502530000, number.national().value());
+    }
+
+    #[test]
+    fn extract_possible_number() {
+        assert_eq!(
+            Some("0800-345-600"),
+            parser::extract_possible_number("Tel:0800-345-600")
+        );
+        assert_eq!(
+            Some("(030) 1234-5678"),
+            parser::extract_possible_number("Tel: (030) 1234-5678.")
+        );
+        assert_eq!(
+            Some("(030) 1234-5678"),
+            parser::extract_possible_number("Tel: (030) 1234-5678 / Fax ...")
+        );
+        assert_eq!(
+            Some("030 1234-5678"),
+            parser::extract_possible_number("Tel: 030 1234-5678 Fax")
+        );
+        assert_eq!(
+            Some("(530) 583-6985 x302"),
+            parser::extract_possible_number("(530) 583-6985 x302/x2303")
+        );
+        assert_eq!(
+            Some("(530) 583-6985 x302"),
+            parser::extract_possible_number("(530) 583-6985 x302 / x2303")
+        );
+        assert_eq!(
+            Some("650-253-0001"),
+            parser::extract_possible_number("650-253-0001 (home)")
+        );
+        // Only an opening bracket the number closes is kept.
+        assert_eq!(
+            Some("030 1234-5678"),
+            parser::extract_possible_number("(Tel: 030 1234-5678)")
+        );
+        // Extensions are kept, only the first number of a list is.
+        assert_eq!(
+            Some("+1-650-253-0000;ext=123"),
+            parser::extract_possible_number("tel:+1-650-253-0000;ext=123")
+        );
+        assert_eq!(
+            Some("+1 650 253 0000, ext. 123"),
+            parser::extract_possible_number("+1 650 253 0000, ext. 123")
+        );
+        assert_eq!(
+            Some("+1 650 253 0000"),
+            parser::extract_possible_number("+1 650 253 0000, 650-253-0001")
+        );
+        // A spaced slash after an area code is punctuation, not a separator.
+        assert_eq!(
+            Some("030 / 123456"),
+            parser::extract_possible_number("030 / 123456")
+        );
+        assert_eq!(
+            Some("030 123456"),
+            parser::extract_possible_number("030 123456 / 030 654321")
+        );
+        assert_eq!(None, parser::extract_possible_number("Num-...."));
+
+        // Where it differs from libphonenumber, which `helper::extract` follows.
+        for (string, ours, theirs) in [
+            (
+                "Tel: (030) 1234-5678 / Fax ...",
+                "(030) 1234-5678",
+                "030) 1234-5678 / Fax",
+            ),
+            ("650-253-0001 (home)", "650-253-0001", "650-253-0001 (home"),
+            ("030 1234-5678 Fax", "030 1234-5678", "030 1234-5678 Fax"),
+            (
+                "+1 650 253 0000, 650-253-0001",
+                "+1 650 253 0000",
+                "+1 650 253 0000, 650-253-0001",
+            ),
+        ] {
+            assert_eq!(Some(ours), parser::extract_possible_number(string));
+            assert_eq!(theirs, helper::extract(string).unwrap().1);
+        }
+    }
+
+    #[test]
+    fn parse_all() {
+        let numbers = parser::parse_all(None, "+1 650 253 0000, 650-253-0001 (home)")
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                parser::parse(None, "+1 650 253 0000").unwrap(),
+                parser::parse(Some(country::US), "650-253-0001").unwrap(),
+            ],
+            numbers
+        );
+
+        let numbers = parser::parse_all(Some(country::US), "(530) 583-6985 x302/x2303")
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(2, numbers.len());
+        assert_eq!(numbers[0].national(), numbers[1].national());
+        assert_eq!("302", &**numbers[0].extension().unwrap());
+        assert_eq!("2303", &**numbers[1].extension().unwrap());
+
+        let numbers = parser::parse_all(
+            Some(country::DE),
+            "030/123456; 030 654321 / +33 1 42 68 53 00",
+        );
+        assert_eq!(3, numbers.len());
+        assert_eq!(49, numbers[1].as_ref().unwrap().code().value());
+        assert_eq!(33, numbers[2].as_ref().unwrap().code().value());
+
+        // A spaced slash after an area code does not split the entry.
+        let numbers = parser::parse_all(Some(country::DE), "030 / 123456");
+        assert_eq!(1, numbers.len());
+        assert_eq!(
+            parser::parse(Some(country::DE), "030 123456").unwrap(),
+            *numbers[0].as_ref().unwrap()
+        );
+
+        // The caller's default country is kept after an international number.
+        let numbers = parser::parse_all(Some(country::DE), "+1 650 253 0000, 030 123456");
+        assert_eq!(2, numbers.len());
+        assert_eq!(1, numbers[0].as_ref().unwrap().code().value());
+        assert_eq!(
+            parser::parse(Some(country::DE), "030 123456").unwrap(),
+            *numbers[1].as_ref().unwrap()
+        );
+
+        let numbers = parser::parse_all(Some(country::US), "650 253 0000, n/a");
+        assert!(numbers[0].is_ok());
+        assert!(numbers[1].is_err());
+
+        // Extensions and RFC3966 parameters are not separators.
+        let numbers = parser::parse_all(None, "tel:+1-650-253-0000;ext=123");
+        assert_eq!(1, numbers.len());
+        let number = numbers[0].as_ref().unwrap();
+        assert_eq!(6502530000, number.national().value());
+        assert_eq!("123", &**number.extension().unwrap());
+
+        let numbers = parser::parse_all(None, "+1 650 253 0000, ext. 123");
+        assert_eq!(1, numbers.len());
+        let number = numbers[0].as_ref().unwrap();
+        assert_eq!(6502530000, number.national().value());
+        assert_eq!("123", &**number.extension().unwrap());
+
+        let numbers = parser::parse_all(
+            None,
+            "tel:+1-650-253-0000;phone-context=example.com, +1 650 253 0001; x456",
+        );
+        assert_eq!(2, numbers.len());
+        assert_eq!(6502530001, numbers[1].as_ref().unwrap().national().value());
+        assert_eq!("456", &**numbers[1].as_ref().unwrap().extension().unwrap());
+
+        // Extensions on their own, in every form the extension pattern takes.
+        for list in [
+            "+1 650 253 0000 / x123",
+            "+1 650 253 0000 / ;ext=123",
+            "+1 650 253 0000 / -123#",
+        ] {
+            let numbers = parser::parse_all(None, list);
+            assert_eq!(2, numbers.len());
+
+            let number = numbers[1].as_ref().unwrap();
+            assert_eq!(6502530000, number.national().value());
+            assert_eq!("123", &**number.extension().unwrap());
+        }
+    }
+
+    #[test]
+    fn parse_with_candidates() {
+        let results = parser::parse_with_candidates(&[country::US, country::GB], "07912 345678");
+        assert_eq!(1, results.len());
+        assert_eq!(country::GB, results[0].0);
+        assert_eq!(Type::Mobile, results[0].2);
+
+        // Valid for both candidates, ties keep the candidate order.
+        let results = parser::parse_with_candidates(&[country::DE, country::GB], "07912 345678");
+        assert_eq!(2, results.len());
+        assert_eq!(country::DE, results[0].0);
+        assert_eq!(country::GB, results[1].0);
+
+        // International numbers end up in their own country whatever the candidate.
+        let results = parser::parse_with_candidates(&[country::DE, country::FR], "+44 7912 345678");
+        assert_eq!(1, results.len());
+        assert_eq!(country::GB, results[0].0);
+
+        assert!(parser::parse_with_candidates(&[country::DE], "no number").is_empty());
+
+        // Local numbers only have a possible length, they are not valid.
+        assert!(parser::parse_with_candidates(&[country::IM, country::GB], "12345").is_empty());
+        assert!(parser::parse_with_candidates(&[country::US], "253 0000").is_empty());
+    }
+
+    #[test]
+    fn excluded_region() {
+        let metadata = loader::load(BufReader::new(
+            File::open("assets/PhoneNumberMetadata.xml").unwrap(),
+        ))
+        .unwrap();
+        let database = Database::from(loader::retain(metadata, ["DE"])).unwrap();
+
+        assert!(parser::parse_with(&database, Some(country::DE), "030 123456").is_ok());
+        assert!(matches!(
+            parser::parse_with(&database, Some(country::US), "650 253 0000"),
+            Err(error::Parse::InvalidCountryCode)
+        ));
+        assert!(matches!(
+            parser::parse_with(&database, Some(country::DE), "+1 650 253 0000"),
+            Err(error::Parse::InvalidCountryCode)
+        ));
+    }
+
     #[test]
     fn issue_43() {
         let res = parser::parse(None, " 2 22#:");
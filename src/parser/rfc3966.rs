@@ -25,7 +25,7 @@ use nom::{
 
 use crate::parser::helper::*;
 
-pub fn phone_number(i: &str) -> IResult<&str, Number> {
+pub fn phone_number(i: &str) -> IResult<&str, Number<'_>> {
     parse! { i =>
         opt(tag_no_case("Tel:"));
         let prefix = opt(prefix);
@@ -61,7 +61,7 @@ pub fn phone_number(i: &str) -> IResult<&str, Number> {
 fn prefix(i: &str) -> IResult<&str, &str> {
     parse! { i =>
         char('+');
-        take_till1(separator)
+        verify(take_till1(separator), |s: &str| s.chars().all(|c| c.is_ascii_digit()))
     }
 }
 
@@ -105,7 +105,7 @@ fn number(c: char) -> bool {
 }
 
 fn digit(c: char) -> bool {
-    c.is_wide_digit() || c.is_hex_digit()
+    c.is_hex_digit()
 }
 
 fn separator(c: char) -> bool {
@@ -164,6 +164,9 @@ mod test {
                 ..Default::default()
             }
         );
+
+        // Natural numbers with spaces are not RFC3966.
+        assert!(rfc3966::phone_number("+1 650-253-0000").is_err());
     }
 
     #[test]
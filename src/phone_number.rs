@@ -12,7 +12,6 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use either::*;
 use std::fmt;
 use std::ops::Deref;
 use std::str::FromStr;
@@ -22,7 +21,7 @@ use crate::country;
 use crate::error;
 use crate::extension::Extension;
 use crate::formatter;
-use crate::metadata::{Database, Metadata, DATABASE};
+use crate::metadata::{Database, Metadata};
 use crate::national_number::NationalNumber;
 use crate::parser;
 use crate::validator;
@@ -78,6 +77,7 @@ pub struct Country<'a>(&'a PhoneNumber);
 /// The phone number type.
 #[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Hash, Debug)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::empty_docs)]
 pub enum Type {
     ///
     FixedLine,
@@ -155,7 +155,7 @@ impl fmt::Display for PhoneNumber {
 
 impl PhoneNumber {
     /// Get information about the country for the phone number.
-    pub fn country(&self) -> Country {
+    pub fn country(&self) -> Country<'_> {
         Country(self)
     }
 
@@ -206,10 +206,7 @@ impl PhoneNumber {
     /// Get the metadata that applies to this phone number from the given
     /// database.
     pub fn metadata<'a>(&self, database: &'a Database) -> Option<&'a Metadata> {
-        match validator::source_for(database, self.code.value(), &self.national.to_string())? {
-            Left(region) => database.by_id(region.as_ref()),
-            Right(code) => database.by_code(&code).and_then(|m| m.into_iter().next()),
-        }
+        validator::metadata_for(database, self.code.value(), &self.national.to_string())
     }
 
     /// Check if the phone number is valid.
@@ -225,7 +222,7 @@ impl PhoneNumber {
     /// Determine the [`Type`] of the phone number.
     pub fn number_type(&self, database: &Database) -> Type {
         match self.metadata(database) {
-            Some(metadata) => validator::number_type(metadata, &self.national.value.to_string()),
+            Some(metadata) => validator::number_type(metadata, &self.national.to_string()),
             None => Type::Unknown,
         }
     }
@@ -237,7 +234,9 @@ impl<'a> Country<'a> {
     }
 
     pub fn id(&self) -> Option<country::Id> {
-        self.0.metadata(&DATABASE).and_then(|m| m.id().parse().ok())
+        self.0
+            .metadata(&Database::snapshot())
+            .and_then(|m| m.id().parse().ok())
     }
 }
 
@@ -252,7 +251,7 @@ impl<'a> Deref for Country<'a> {
 #[cfg(test)]
 mod test {
     use crate::country::{self, *};
-    use crate::metadata::DATABASE;
+    use crate::metadata::DEFAULT as DATABASE;
     use crate::Type;
     use crate::{parser, Mode, PhoneNumber};
     use anyhow::Context;
@@ -275,6 +274,8 @@ mod test {
     #[case(parsed("+34612345678"), Some(ES), Type::Mobile)]
     #[case(parsed("+441212345678"), Some(GB), Type::FixedLine)]
     #[case(parsed("+13459492311"), Some(KY), Type::FixedLine)]
+    // The leading zero of Italian numbers is part of the national number.
+    #[case(parsed("+390236618300"), Some(IT), Type::FixedLine)]
     #[case(parsed("+16137827274"), Some(CA), Type::FixedLineOrMobile)]
     #[case(parsed("+1 520 878 2491"), Some(US), Type::FixedLineOrMobile)]
     #[case(parsed("+1-520-878-2491"), Some(US), Type::FixedLineOrMobile)]
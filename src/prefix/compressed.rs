@@ -0,0 +1,105 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use bincode::Options;
+use fnv::FnvHashMap;
+
+use crate::prefix::Trie;
+
+/// Descriptions by number prefix embedded by the build script, per language
+/// and country code.
+///
+/// Every language and country code is compressed on its own, and only
+/// decompressed the first time it is looked up.
+#[derive(Debug)]
+pub struct Prefixes {
+    languages: FnvHashMap<&'static str, FnvHashMap<u16, Map>>,
+}
+
+/// The descriptions for a language and country code, possibly still
+/// compressed.
+struct Map {
+    compressed: &'static [u8],
+    trie: OnceLock<Trie>,
+}
+
+/// A compressed map in the embedded data, as written by the build script.
+#[derive(Deserialize)]
+struct Encoded<'a> {
+    language: &'a str,
+    code: u16,
+    #[serde(borrow)]
+    trie: &'a [u8],
+}
+
+impl Prefixes {
+    /// Index the embedded compressed maps, without decompressing them.
+    pub fn embedded(bytes: &'static [u8]) -> Self {
+        let maps: Vec<Encoded<'static>> = bincode::options()
+            .with_varint_encoding()
+            .deserialize(bytes)
+            .expect("failed to decode embedded prefixes");
+
+        let mut languages = FnvHashMap::<_, FnvHashMap<_, _>>::default();
+
+        for map in maps {
+            languages.entry(map.language).or_default().insert(
+                map.code,
+                Map {
+                    compressed: map.trie,
+                    trie: OnceLock::new(),
+                },
+            );
+        }
+
+        Prefixes { languages }
+    }
+
+    /// The embedded languages.
+    pub fn languages(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.languages.keys().copied()
+    }
+
+    /// Get the description for the longest prefix of the given digits, which
+    /// start with the country code.
+    pub fn get(&self, language: &str, code: u16, digits: &str) -> Option<&str> {
+        self.languages.get(language)?.get(&code)?.get().get(digits)
+    }
+}
+
+impl Map {
+    fn get(&self) -> &Trie {
+        self.trie.get_or_init(|| {
+            let bytes = miniz_oxide::inflate::decompress_to_vec(self.compressed)
+                .expect("failed to decompress embedded prefixes");
+
+            bincode::options()
+                .with_varint_encoding()
+                .deserialize(&bytes)
+                .expect("failed to decode embedded prefixes")
+        })
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.trie.get() {
+            Some(trie) => fmt::Debug::fmt(trie, f),
+            None => f.write_str("<compressed>"),
+        }
+    }
+}
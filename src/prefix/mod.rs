@@ -0,0 +1,64 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod trie;
+pub use self::trie::{entries, Trie};
+
+#[cfg(any(feature = "geocoding", feature = "carrier"))]
+mod compressed;
+#[cfg(any(feature = "geocoding", feature = "carrier"))]
+pub use self::compressed::Prefixes;
+
+/// The languages to look up for the given language tag, e.g. `zh-TW` is
+/// looked up as `zh_Hant`, `de-CH` as `de`, and `he` as `iw` like in
+/// libphonenumber.
+pub fn candidates(language: &str) -> Vec<String> {
+    let mut parts = language.split(['-', '_']);
+    let base = parts.next().unwrap_or_default().to_lowercase();
+    let rest = parts.collect::<Vec<_>>();
+
+    let mut result = Vec::new();
+
+    if base == "zh"
+        && rest.iter().any(|p| {
+            p.eq_ignore_ascii_case("hant")
+                || p.eq_ignore_ascii_case("tw")
+                || p.eq_ignore_ascii_case("hk")
+                || p.eq_ignore_ascii_case("mo")
+        })
+    {
+        result.push("zh_Hant".to_owned());
+    }
+
+    result.push(match base.as_str() {
+        "he" => "iw".to_owned(),
+        _ => base,
+    });
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prefix::candidates;
+
+    #[test]
+    fn languages() {
+        assert_eq!(vec!["de"], candidates("de-CH"));
+        assert_eq!(vec!["zh_Hant", "zh"], candidates("zh-TW"));
+        assert_eq!(vec!["zh_Hant", "zh"], candidates("zh_Hant"));
+        assert_eq!(vec!["zh"], candidates("zh"));
+        assert_eq!(vec!["iw"], candidates("he-IL"));
+    }
+}
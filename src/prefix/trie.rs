@@ -0,0 +1,228 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::error;
+
+/// Descriptions by number prefix, for one language and country code.
+///
+/// The nodes are flattened into a single vector, the root first. Every node
+/// is made of the index of its description plus one, or zero if it has none,
+/// the bit mask of the digits it has children for, and then the index of
+/// every child in order of digit.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Trie {
+    descriptions: Vec<String>,
+    nodes: Vec<u32>,
+}
+
+/// A node of the trie while it is being built.
+#[derive(Default)]
+struct Node {
+    description: Option<u32>,
+    children: [Option<usize>; 10],
+}
+
+impl Trie {
+    /// Build a trie from the prefixes and their descriptions.
+    ///
+    /// Prefixes must only be made of ASCII digits, the error names the first
+    /// entry with any other character.
+    pub fn new<I, P, D>(entries: I) -> Result<Self, error::LoadMetadata>
+    where
+        I: IntoIterator<Item = (P, D)>,
+        P: AsRef<str>,
+        D: Into<String>,
+    {
+        let mut descriptions = Vec::new();
+        let mut indices = HashMap::new();
+        let mut tree = vec![Node::default()];
+
+        for (i, (prefix, description)) in entries.into_iter().enumerate() {
+            let prefix = prefix.as_ref();
+            let mut node = 0;
+
+            if prefix.is_empty() || !prefix.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(error::LoadMetadata::malformed(
+                    "prefix file",
+                    format!("invalid prefix {:?} in entry {}", prefix, i + 1),
+                ));
+            }
+
+            for digit in prefix.bytes().map(|b| usize::from(b - b'0')) {
+                node = match tree[node].children[digit] {
+                    Some(child) => child,
+
+                    None => {
+                        tree.push(Node::default());
+                        tree[node].children[digit] = Some(tree.len() - 1);
+                        tree.len() - 1
+                    }
+                };
+            }
+
+            let description = description.into();
+            let index = *indices.entry(description.clone()).or_insert_with(|| {
+                descriptions.push(description);
+                descriptions.len() as u32 - 1
+            });
+
+            tree[node].description = Some(index);
+        }
+
+        let mut nodes = Vec::new();
+        flatten(&tree, 0, &mut nodes);
+
+        Ok(Trie {
+            descriptions,
+            nodes,
+        })
+    }
+
+    /// Get the description for the longest prefix of the given digits.
+    #[allow(unused)] // This is unused in the build script.
+    pub fn get(&self, digits: &str) -> Option<&str> {
+        let mut node = 0;
+        let mut found = None;
+
+        for digit in digits.bytes().map(|b| b.wrapping_sub(b'0')) {
+            if self.nodes[node] != 0 {
+                found = Some(self.nodes[node]);
+            }
+
+            let mask = self.nodes[node + 1];
+
+            if digit > 9 || mask & (1 << digit) == 0 {
+                return self.description(found);
+            }
+
+            let child = (mask & ((1 << digit) - 1)).count_ones() as usize;
+            node = self.nodes[node + 2 + child] as usize;
+        }
+
+        if self.nodes[node] != 0 {
+            found = Some(self.nodes[node]);
+        }
+
+        self.description(found)
+    }
+
+    fn description(&self, index: Option<u32>) -> Option<&str> {
+        index.map(|i| &*self.descriptions[i as usize - 1])
+    }
+}
+
+/// Parse the prefixes and their descriptions in the libphonenumber prefix
+/// files, such as `geocoding/en/1.txt` or `timezones/map_data.txt`.
+pub fn entries(content: &str) -> Result<Vec<(&str, &str)>, error::LoadMetadata> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line.split_once('|') {
+            Some((prefix, description))
+                if !prefix.is_empty() && prefix.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                entries.push((prefix, description))
+            }
+
+            _ => {
+                return Err(error::LoadMetadata::malformed(
+                    "prefix file",
+                    format!("expected `prefix|description`, found {:?}", line),
+                )
+                .at_offset(start, content.as_bytes()))
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Append the node and its children to the flattened nodes, returning its
+/// index.
+fn flatten(tree: &[Node], node: usize, nodes: &mut Vec<u32>) -> u32 {
+    let index = nodes.len();
+    let children = tree[node]
+        .children
+        .iter()
+        .enumerate()
+        .filter_map(|(digit, child)| child.map(|c| (digit, c)))
+        .collect::<Vec<_>>();
+
+    nodes.push(tree[node].description.map_or(0, |d| d + 1));
+    nodes.push(
+        children
+            .iter()
+            .fold(0, |mask, (digit, _)| mask | 1 << digit),
+    );
+    nodes.extend(children.iter().map(|_| 0));
+
+    for (i, (_, child)) in children.into_iter().enumerate() {
+        nodes[index + 2 + i] = flatten(tree, child, nodes);
+    }
+
+    index as u32
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prefix::{entries, Trie};
+
+    #[test]
+    fn trie() {
+        let trie = Trie::new([
+            ("1201", "New Jersey"),
+            ("1212", "New York, NY"),
+            ("12125", "Manhattan"),
+            ("1646", "New York, NY"),
+        ])
+        .unwrap();
+
+        assert_eq!(Some("New Jersey"), trie.get("12015550123"));
+        assert_eq!(Some("New York, NY"), trie.get("12124567890"));
+        assert_eq!(Some("Manhattan"), trie.get("12125567890"));
+        assert_eq!(Some("New York, NY"), trie.get("1646"));
+        assert_eq!(None, trie.get("164"));
+        assert_eq!(None, trie.get("13105550123"));
+        assert_eq!(None, trie.get(""));
+
+        let error = Trie::new([("1201", "New Jersey"), ("12 12", "New York, NY")]).unwrap_err();
+        assert!(error.to_string().contains("\"12 12\" in entry 2"));
+        assert!(Trie::new([("", "Anywhere")]).is_err());
+    }
+
+    #[test]
+    fn parse() {
+        let content = "# Comment\n\n1201|New Jersey\r\n1212|New York, NY\n";
+
+        assert_eq!(
+            vec![("1201", "New Jersey"), ("1212", "New York, NY")],
+            entries(content).unwrap()
+        );
+
+        let error = entries("1201|New Jersey\n1212 New York\n").unwrap_err();
+        assert_eq!(Some(2), error.location().unwrap().line);
+    }
+}
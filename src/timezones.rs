@@ -0,0 +1,199 @@
+// Copyright (C) 2017 1aim GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "timezones")]
+use bincode::Options;
+
+use crate::error;
+use crate::metadata::Database;
+use crate::phone_number::{PhoneNumber, Type};
+use crate::prefix::{self, Trie};
+use crate::validator;
+
+#[cfg(feature = "timezones")]
+const TIMEZONES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/timezones.bin"));
+
+#[cfg(feature = "timezones")]
+lazy_static! {
+    /// The time zones in `assets/timezones/map_data.txt`, embedded with the
+    /// `timezones` feature.
+    pub static ref DEFAULT: Timezones = Timezones {
+        trie: bincode::options()
+            .with_varint_encoding()
+            .deserialize(TIMEZONES)
+            .expect("failed to decode embedded time zones"),
+    };
+}
+
+/// IANA time zone names by number prefix, as in libphonenumber's
+/// `timezones/map_data.txt`.
+#[derive(Clone, Debug)]
+pub struct Timezones {
+    trie: Trie,
+}
+
+impl Timezones {
+    /// Load the time zones from the given file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, error::LoadMetadata> {
+        Timezones::parse(fs::read_to_string(path)?)
+    }
+
+    /// Parse the time zones from the given string.
+    pub fn parse<S: AsRef<str>>(content: S) -> Result<Self, error::LoadMetadata> {
+        Ok(Timezones {
+            trie: Trie::new(prefix::entries(content.as_ref())?)?,
+        })
+    }
+
+    /// Get the time zones the phone number may be in.
+    ///
+    /// Geographical numbers are looked up by their longest known prefix, other
+    /// numbers get every time zone of their country. Invalid numbers and
+    /// numbers without a known time zone get none, where libphonenumber
+    /// returns `Etc/Unknown`.
+    pub fn for_number(&self, number: &PhoneNumber) -> Vec<&str> {
+        self.for_number_with(&Database::snapshot(), number)
+    }
+
+    /// Get the time zones the phone number may be in, with the given
+    /// `Database`.
+    pub fn for_number_with(&self, database: &Database, number: &PhoneNumber) -> Vec<&str> {
+        let code = number.code().value();
+        let digits = match number.number_type(database) {
+            Type::Unknown => return Vec::new(),
+
+            kind if validator::is_geographical(kind, code) => {
+                format!("{}{}", code, number.national())
+            }
+
+            _ => code.to_string(),
+        };
+
+        self.trie
+            .get(&digits)
+            .map(|zones| zones.split('&').collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Get the time zones the phone number may be in from the embedded time
+/// zones, see `Timezones::for_number`.
+#[cfg(feature = "timezones")]
+pub fn for_number(number: &PhoneNumber) -> Vec<&'static str> {
+    DEFAULT.for_number(number)
+}
+
+/// Get the time zones the phone number may be in from the embedded time
+/// zones with the given `Database`, see `Timezones::for_number`.
+#[cfg(feature = "timezones")]
+pub fn for_number_with(database: &Database, number: &PhoneNumber) -> Vec<&'static str> {
+    DEFAULT.for_number_with(database, number)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::country;
+    use crate::parser;
+    use crate::timezones::Timezones;
+
+    const MAP: &str = "
+# Generated from the IANA time zone database.
+
+1|America/Adak&America/Anchorage&America/Chicago&America/Denver&America/Los_Angeles&America/New_York
+1201|America/New_York
+1213|America/Los_Angeles
+1650|America/Los_Angeles
+7|Asia/Kamchatka&Asia/Vladivostok&Europe/Moscow
+7495|Europe/Moscow
+61|Australia/Adelaide&Australia/Perth&Australia/Sydney
+612|Australia/Sydney
+";
+
+    #[test]
+    fn geographical() {
+        let timezones = Timezones::parse(MAP).unwrap();
+
+        let number = parser::parse(None, "+1 201 555 0123").unwrap();
+        assert_eq!(vec!["America/New_York"], timezones.for_number(&number));
+
+        let number = parser::parse(None, "+1 213 373 4253").unwrap();
+        assert_eq!(vec!["America/Los_Angeles"], timezones.for_number(&number));
+
+        let number = parser::parse(None, "+1 650 253 0000").unwrap();
+        assert_eq!(vec!["America/Los_Angeles"], timezones.for_number(&number));
+
+        let number = parser::parse(Some(country::RU), "8 495 123 45 67").unwrap();
+        assert_eq!(vec!["Europe/Moscow"], timezones.for_number(&number));
+
+        let number = parser::parse(Some(country::AU), "02 1234 5678").unwrap();
+        assert_eq!(vec!["Australia/Sydney"], timezones.for_number(&number));
+    }
+
+    #[test]
+    fn country_level() {
+        let timezones = Timezones::parse(MAP).unwrap();
+
+        let number = parser::parse(None, "+1 800 253 0000").unwrap();
+        assert_eq!(6, timezones.for_number(&number).len());
+
+        let number = parser::parse(Some(country::AU), "0412 345 678").unwrap();
+        assert_eq!(
+            vec!["Australia/Adelaide", "Australia/Perth", "Australia/Sydney"],
+            timezones.for_number(&number)
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        let timezones = Timezones::parse(MAP).unwrap();
+
+        let number = parser::parse(None, "+1 201 555").unwrap();
+        assert!(timezones.for_number(&number).is_empty());
+
+        let number = parser::parse(None, "+49 30 123456").unwrap();
+        assert!(timezones.for_number(&number).is_empty());
+    }
+
+    #[test]
+    fn malformed() {
+        let error = Timezones::parse("1|America/New_York\n1201\n").unwrap_err();
+        assert_eq!(Some(2), error.location().unwrap().line);
+    }
+
+    #[test]
+    #[cfg(feature = "timezones")]
+    fn embedded() {
+        use crate::timezones;
+
+        let zones = |country, number| {
+            timezones::for_number(&parser::parse(Some(country), number).unwrap())
+        };
+
+        assert_eq!(
+            vec!["America/Los_Angeles"],
+            zones(country::US, "+1 650 253 0000")
+        );
+        assert_eq!(vec!["America/New_York"], zones(country::US, "212 555 0123"));
+        assert_eq!(vec!["America/Toronto"], zones(country::CA, "416 555 0123"));
+        assert_eq!(vec!["America/Vancouver"], zones(country::CA, "604 555 0123"));
+        assert_eq!(vec!["Europe/Moscow"], zones(country::RU, "8 495 123 45 67"));
+        assert_eq!(vec!["Australia/Sydney"], zones(country::AU, "02 1234 5678"));
+
+        // Mobile numbers are not tied to an area.
+        assert!(zones(country::AU, "0412 345 678").len() > 1);
+    }
+}
@@ -12,11 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use either::*;
-
 use crate::consts;
-use crate::country;
-use crate::metadata::{Database, Metadata, DATABASE};
+use crate::metadata::{Database, Metadata};
 use crate::parser;
 use crate::parser::helper::Number as ParseNumber;
 use crate::phone_number::{PhoneNumber, Type};
@@ -90,22 +87,20 @@ pub fn is_viable<S: AsRef<str>>(string: S) -> bool {
 
 /// Check if the phone number is valid.
 pub fn is_valid(number: &PhoneNumber) -> bool {
-    is_valid_with(&DATABASE, number)
+    is_valid_with(&Database::snapshot(), number)
 }
 
 /// Check if the phone number is valid with the given `Database`.
 pub fn is_valid_with(database: &Database, number: &PhoneNumber) -> bool {
     let code = number.country().code();
     let national = number.national.to_string();
-    source_for(database, code, &national)
-        .and_then(|meta| match meta {
-            Left(region) => database.by_id(region.as_ref()),
-            Right(code) => database.by_code(&code).and_then(|m| m.into_iter().next()),
-        })
+    metadata_for(database, code, &national)
         .map(|meta| number_type(meta, &national) != Type::Unknown)
         .unwrap_or(false)
 }
 
+/// Check the length of the national number against the possible lengths of
+/// the given type, or of the general descriptor when the type has none.
 pub fn length(meta: &Metadata, number: &ParseNumber, kind: Type) -> Validation {
     let desc = if let Some(desc) = meta.descriptors().get(kind) {
         desc
@@ -114,11 +109,12 @@ pub fn length(meta: &Metadata, number: &ParseNumber, kind: Type) -> Validation {
     };
 
     let length = number.national.len() as u16;
-    let local = &desc.possible_local_length[..];
-    let possible = if desc.possible_length.is_empty() {
-        &desc.possible_length[..]
+    let (possible, local) = if kind == Type::Unknown {
+        (&meta.general_lengths.0, &meta.general_lengths.1)
+    } else if desc.possible_length.is_empty() {
+        (&meta.general_lengths.0, &desc.possible_local_length)
     } else {
-        &meta.descriptors.general.possible_length[..]
+        (&desc.possible_length, &desc.possible_local_length)
     };
 
     if possible.is_empty() {
@@ -142,39 +138,35 @@ pub fn length(meta: &Metadata, number: &ParseNumber, kind: Type) -> Validation {
     }
 }
 
-/// Find the metadata source.
-pub fn source_for(
-    database: &Database,
+/// Check if numbers of the given type are assigned to a geographical area in
+/// the country with the given calling code.
+pub fn is_geographical(kind: Type, code: u16) -> bool {
+    match kind {
+        Type::FixedLine | Type::FixedLineOrMobile => true,
+        Type::Mobile => consts::GEO_MOBILE_COUNTRIES.contains(&code),
+        _ => false,
+    }
+}
+
+/// Find the metadata for a national number with the given country code,
+/// including regions whose ID is not a known `country::Id`.
+pub fn metadata_for<'a>(
+    database: &'a Database,
     code: u16,
     national: &str,
-) -> Option<Either<country::Id, u16>> {
-    let regions = database.region(&code)?;
-    if regions.len() == 1 {
-        return if regions[0] == "001" {
-            Some(Right(code))
-        } else {
-            match regions[0].parse() {
-                Ok(value) => Some(Left(value)),
-                Err(_) => None,
-            }
-        };
-    }
+) -> Option<&'a Metadata> {
+    let regions = database.by_code(&code)?;
 
-    for region in regions {
-        let meta = database.by_id(region).unwrap();
-
-        if let Some(pattern) = meta.leading_digits.as_ref() {
-            if let Some(index) = pattern.find(national) {
-                if index.start() == 0 {
-                    return Some(Left(region.parse().unwrap()));
-                }
-            }
-        } else if number_type(meta, national) != Type::Unknown {
-            return Some(Left(region.parse().unwrap()));
-        }
+    if regions.len() == 1 {
+        return regions.into_iter().next();
     }
 
-    None
+    regions
+        .into_iter()
+        .find(|meta| match meta.leading_digits.as_ref() {
+            Some(pattern) => pattern.match_at_start(national).is_some(),
+            None => number_type(meta, national) != Type::Unknown,
+        })
 }
 
 pub fn number_type(meta: &Metadata, value: &str) -> Type {
@@ -273,12 +265,12 @@ pub fn number_type(meta: &Metadata, value: &str) -> Type {
             .descriptors
             .fixed_line
             .as_ref()
-            .map(|d| d.national_number.as_str())
+            .map(|d| d.national_number.source())
             == meta
                 .descriptors
                 .mobile
                 .as_ref()
-                .map(|d| d.national_number.as_str())
+                .map(|d| d.national_number.source())
         {
             return Type::FixedLineOrMobile;
         }
@@ -369,4 +361,54 @@ mod test {
             &parser::parse(None, "+800 123456789").unwrap()
         ));
     }
+
+    #[test]
+    fn length() {
+        use crate::metadata::DEFAULT as DATABASE;
+        use crate::parser::helper::Number;
+        use crate::phone_number::Type;
+        use crate::validator::Validation;
+
+        let meta = DATABASE.by_id("US").unwrap();
+        let length = |national: &str, kind| {
+            let number = Number {
+                national: national.into(),
+                ..Default::default()
+            };
+
+            validator::length(meta, &number, kind)
+        };
+
+        // Without a type, the lengths of every type are used.
+        assert_eq!(Validation::IsPossible, length("6502530000", Type::Unknown));
+        assert_eq!(
+            Validation::IsPossibleLocalOnly,
+            length("2530000", Type::Unknown)
+        );
+        assert_eq!(Validation::TooShort, length("253000", Type::Unknown));
+        assert_eq!(Validation::TooLong, length("65025300000", Type::Unknown));
+
+        // A type with lengths of its own only uses those.
+        assert_eq!(Validation::IsPossible, length("8002530000", Type::TollFree));
+        assert_eq!(Validation::TooShort, length("2530000", Type::TollFree));
+    }
+
+    #[test]
+    fn validate_threads() {
+        let threads = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    for _ in 0..100 {
+                        assert!(validator::is_valid(
+                            &parser::parse(Some(country::GB), "+44 7912345678").unwrap()
+                        ));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
 }